@@ -0,0 +1,90 @@
+// spawn/despawn vs pooled lasers, run with `cargo bench --bench pool`
+use std::{collections::VecDeque, time::Instant};
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use undead_germ4ns::pool::{EntityPool, PoolKind};
+
+const FRAMES: usize = 600;
+const LASERS_PER_FRAME: usize = 300;
+const LASER_LIFETIME: usize = 60; // frames on screen before leaving
+
+#[derive(Component)]
+struct Laser;
+
+fn laser_bundle(i: usize) -> SpriteBundle {
+    SpriteBundle {
+        transform: Transform::from_xyz(i as f32, 0., 2.),
+        ..Default::default()
+    }
+}
+
+// runs one frame worth of commands against the world
+fn frame(world: &mut World, f: impl FnOnce(&mut Commands)) {
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    f(&mut commands);
+    queue.apply(world);
+}
+
+fn spawn_despawn() -> f64 {
+    let mut world = World::new();
+    let mut alive: VecDeque<Vec<Entity>> = VecDeque::new();
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame(&mut world, |commands| {
+            let spawned = (0..LASERS_PER_FRAME)
+                .map(|i| commands.spawn_bundle(laser_bundle(i)).insert(Laser).id())
+                .collect();
+            alive.push_back(spawned);
+
+            if alive.len() > LASER_LIFETIME {
+                for entity in alive.pop_front().unwrap() {
+                    commands.entity(entity).despawn();
+                }
+            }
+        });
+    }
+    start.elapsed().as_secs_f64()
+}
+
+fn pooled() -> f64 {
+    let mut world = World::new();
+    let mut pool = EntityPool::default();
+    let mut alive: VecDeque<Vec<Entity>> = VecDeque::new();
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        frame(&mut world, |commands| {
+            let spawned = (0..LASERS_PER_FRAME)
+                .map(|i| {
+                    pool.acquire(commands, PoolKind::PlayerLaser, laser_bundle(i))
+                        .insert(Laser)
+                        .id()
+                })
+                .collect();
+            alive.push_back(spawned);
+
+            if alive.len() > LASER_LIFETIME {
+                for entity in alive.pop_front().unwrap() {
+                    pool.release(commands, entity, PoolKind::PlayerLaser);
+                }
+            }
+        });
+    }
+    start.elapsed().as_secs_f64()
+}
+
+fn main() {
+    println!(
+        "{} frames, {} lasers per frame, {} frames lifetime",
+        FRAMES, LASERS_PER_FRAME, LASER_LIFETIME
+    );
+
+    let despawn_secs = spawn_despawn();
+    let pooled_secs = pooled();
+
+    println!("spawn/despawn: {:>8.2} ms", despawn_secs * 1000.);
+    println!("pooled:        {:>8.2} ms", pooled_secs * 1000.);
+    println!("speedup:       {:>8.2}x", despawn_secs / pooled_secs);
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use crate::GameClock;
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationFinished>()
+            .add_system(sprite_animation_system);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayMode {
+    Loop,
+    Once,     // holds the last frame and sends AnimationFinished
+    PingPong, // first..last..first.., loops
+}
+
+// frames first..=last of the atlas, same duration each
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationClip {
+    pub name: &'static str,
+    pub first: usize,
+    pub last: usize,
+    pub frame_time: f32, // seconds
+    pub mode: PlayMode,
+}
+
+impl AnimationClip {
+    fn len(&self) -> usize {
+        self.last - self.first + 1
+    }
+}
+
+// -Event - a Once clip played through
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: &'static str,
+}
+
+// -- Component - steps the TextureAtlasSprite of the entity through named clips
+#[derive(Component)]
+pub struct SpriteAnimation {
+    clips: &'static [AnimationClip],
+    current: usize,
+    frame: usize, // within the clip
+    forward: bool,
+    timer: Timer,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    /// starts the first clip
+    pub fn new(clips: &'static [AnimationClip]) -> Self {
+        let mut animation = Self {
+            clips,
+            current: 0,
+            frame: 0,
+            forward: true,
+            timer: Timer::from_seconds(clips[0].frame_time, true),
+            finished: false,
+        };
+        animation.restart();
+        animation
+    }
+
+    /// switches clip, no-op if it is already playing. unknown names are ignored
+    pub fn play(&mut self, name: &str) {
+        if self.clip() == name {
+            return;
+        }
+        if let Some(index) = self.clips.iter().position(|clip| clip.name == name) {
+            self.current = index;
+            self.restart();
+        } else {
+            warn!("no animation clip named {}", name);
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.forward = true;
+        self.finished = false;
+        self.timer = Timer::from_seconds(self.clips[self.current].frame_time, true);
+    }
+
+    pub fn clip(&self) -> &'static str {
+        self.clips[self.current].name
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// atlas index to show
+    pub fn index(&self) -> usize {
+        self.clips[self.current].first + self.frame
+    }
+
+    // one frame on, true if a Once clip just ended
+    fn step(&mut self) -> bool {
+        let clip = self.clips[self.current];
+        let last = clip.len() - 1;
+        match clip.mode {
+            PlayMode::Loop => self.frame = (self.frame + 1) % clip.len(),
+            PlayMode::Once => {
+                // the last frame gets its full time too
+                if self.frame < last {
+                    self.frame += 1;
+                } else {
+                    self.finished = true;
+                    return true;
+                }
+            }
+            PlayMode::PingPong => {
+                if last == 0 {
+                    return false;
+                }
+                if self.forward && self.frame == last {
+                    self.forward = false;
+                } else if !self.forward && self.frame == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.frame += 1;
+                } else {
+                    self.frame -= 1;
+                }
+            }
+        }
+        false
+    }
+}
+
+fn sprite_animation_system(
+    clock: Res<GameClock>,
+    mut finished_events: EventWriter<AnimationFinished>,
+    mut query: Query<(Entity, &mut SpriteAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (entity, mut animation, mut sprite) in query.iter_mut() {
+        if animation.finished {
+            continue;
+        }
+        animation.timer.tick(clock.delta());
+        // slow frames can skip more than one step
+        for _ in 0..animation.timer.times_finished_this_tick() {
+            if animation.step() {
+                finished_events.send(AnimationFinished {
+                    entity,
+                    clip: animation.clip(),
+                });
+                break;
+            }
+        }
+        if sprite.index != animation.index() {
+            sprite.index = animation.index();
+        }
+    }
+}
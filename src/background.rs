@@ -0,0 +1,225 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    game_reset_system, AppState, GameClock, GameRng, Wave, WinSize, BG_SCROLL_MAX,
+    BG_SCROLL_PER_WAVE, BG_SCROLL_SPEED,
+};
+
+const NEBULA_TEXTURE_SIZE: usize = 128;
+const NEBULA_GRID: usize = 8; // noise cells per side, tiles seamlessly
+
+struct StarLayer {
+    count: u32,
+    size: (f32, f32), // min/max
+    brightness: f32,
+    depth: f32, // scroll factor, 1 is the nearest layer
+    z: f32,
+}
+
+const STAR_LAYERS: [StarLayer; 3] = [
+    StarLayer {
+        count: 160,
+        size: (1., 1.5),
+        brightness: 0.35,
+        depth: 0.2,
+        z: -30.,
+    },
+    StarLayer {
+        count: 80,
+        size: (1.5, 2.5),
+        brightness: 0.6,
+        depth: 0.5,
+        z: -20.,
+    },
+    StarLayer {
+        count: 30,
+        size: (2.5, 3.5),
+        brightness: 1.,
+        depth: 1.,
+        z: -10.,
+    },
+];
+const NEBULA_DEPTH: f32 = 0.1;
+const NEBULA_Z: f32 = -40.;
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BackgroundScroll {
+            speed: BG_SCROLL_SPEED,
+        })
+        .add_system_set(
+            SystemSet::on_enter(AppState::Playing)
+                .with_system(background_setup_system.after(game_reset_system)),
+        )
+        .add_system(background_speed_system)
+        .add_system(background_scroll_system.after(background_speed_system));
+    }
+}
+
+// -Ressource - px per second of the nearest layer, picks up with the waves
+struct BackgroundScroll {
+    speed: f32,
+}
+
+// region: --- Components
+#[derive(Component)]
+struct Parallax {
+    depth: f32,
+    wrap: f32, // height of one full cycle
+    half_height: f32,
+}
+// endregion: --- Components
+
+// new sky for every game, seeded like the rest of the run
+fn background_setup_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    game_rng: Res<GameRng>,
+    win_size: Res<WinSize>,
+    query: Query<Entity, With<Parallax>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.insert_resource(BackgroundScroll {
+        speed: BG_SCROLL_SPEED,
+    });
+
+    // own generator, drawing stars must not shift the gameplay sequence
+    let mut rng = StdRng::seed_from_u64(game_rng.seed);
+    let (w, h) = (win_size.w, win_size.h);
+
+    for layer in STAR_LAYERS.iter() {
+        for _ in 0..layer.count {
+            let x = rng.gen_range(-w / 2.0..w / 2.);
+            let y = rng.gen_range(-h / 2.0..h / 2.);
+            let size = rng.gen_range(layer.size.0..=layer.size.1);
+            // a bit of blue or orange in some stars
+            let tint = rng.gen_range(-0.15..0.15);
+            let b = layer.brightness;
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(b * (1. + tint), b, b * (1. - tint)),
+                        custom_size: Some(Vec2::splat(size)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(x, y, layer.z),
+                    ..Default::default()
+                })
+                .insert(Parallax {
+                    depth: layer.depth,
+                    wrap: h,
+                    half_height: 0.,
+                });
+        }
+    }
+
+    // two stacked nebula tiles, the texture wraps vertically
+    let nebula = images.add(nebula_image(&mut rng));
+    for i in 0..2 {
+        commands
+            .spawn_bundle(SpriteBundle {
+                texture: nebula.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(w, h)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., i as f32 * h, NEBULA_Z),
+                ..Default::default()
+            })
+            .insert(Parallax {
+                depth: NEBULA_DEPTH,
+                wrap: 2. * h,
+                half_height: h / 2.,
+            });
+    }
+}
+
+/// soft two color clouds from tiling value noise
+fn nebula_image(rng: &mut StdRng) -> Image {
+    let mut lattice = [[0f32; NEBULA_GRID]; NEBULA_GRID];
+    for row in lattice.iter_mut() {
+        for value in row.iter_mut() {
+            *value = rng.gen();
+        }
+    }
+    let colors = [
+        Vec3::new(rng.gen_range(0.2..0.5), 0.1, rng.gen_range(0.4..0.7)),
+        Vec3::new(0.1, rng.gen_range(0.2..0.4), rng.gen_range(0.4..0.6)),
+    ];
+
+    let noise = |x: f32, y: f32| {
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (fx, fy) = (x.fract(), y.fract());
+        let (sx, sy) = (fx * fx * (3. - 2. * fx), fy * fy * (3. - 2. * fy));
+        let at = |i: usize, j: usize| lattice[j % NEBULA_GRID][i % NEBULA_GRID];
+        let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * sx;
+        let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * sx;
+        top + (bottom - top) * sy
+    };
+
+    let size = NEBULA_TEXTURE_SIZE;
+    let mut data = Vec::with_capacity(size * size * 4);
+    for py in 0..size {
+        for px in 0..size {
+            let (x, y) = (px as f32, py as f32);
+            let cell = (size / NEBULA_GRID) as f32;
+            // two octaves, the second one still tiles (grid * 2 wraps at the same edge)
+            let density =
+                noise(x / cell, y / cell) * 0.7 + noise(x / cell * 2., y / cell * 2.) * 0.3;
+            let hue = noise(x / cell + 3.5, y / cell + 3.5);
+            let color = colors[0].lerp(colors[1], hue);
+            let alpha = ((density - 0.45) * 2.).clamp(0., 1.) * 0.35;
+            data.extend_from_slice(&[
+                (color.x * 255.) as u8,
+                (color.y * 255.) as u8,
+                (color.z * 255.) as u8,
+                (alpha * 255.) as u8,
+            ]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size as u32,
+            height: size as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn background_speed_system(
+    clock: Res<GameClock>,
+    wave: Res<Wave>,
+    mut scroll: ResMut<BackgroundScroll>,
+) {
+    // ease toward the speed of the current wave
+    let target = (BG_SCROLL_SPEED + BG_SCROLL_PER_WAVE * wave.0 as f32).min(BG_SCROLL_MAX);
+    scroll.speed += (target - scroll.speed) * clock.delta_seconds().min(1.);
+}
+
+fn background_scroll_system(
+    clock: Res<GameClock>,
+    scroll: Res<BackgroundScroll>,
+    win_size: Res<WinSize>,
+    mut query: Query<(&Parallax, &mut Transform)>,
+) {
+    let bottom = -win_size.h / 2.;
+    for (parallax, mut transform) in query.iter_mut() {
+        transform.translation.y -= scroll.speed * parallax.depth * clock.delta_seconds();
+        // back to the top once fully off screen
+        if transform.translation.y + parallax.half_height < bottom {
+            transform.translation.y += parallax.wrap;
+        }
+    }
+}
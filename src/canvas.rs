@@ -0,0 +1,174 @@
+use bevy::{
+    prelude::*,
+    render::camera::Viewport,
+    window::{WindowId, WindowMode, WindowMoved, WindowResized, WindowScaleFactorChanged},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{platform, ArenaBounds, WinSize, VIRTUAL_HEIGHT, VIRTUAL_WIDTH, WINDOW_SAVE_DELAY};
+
+const WINDOW_SETTINGS_KEY: &str = "window";
+
+// -- fixed virtual play area scaled into the window, the rest stays letterboxed
+
+pub struct CanvasPlugin;
+
+impl Plugin for CanvasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(canvas_resize_system)
+            .add_system(fullscreen_toggle_system)
+            .add_system(window_settings_system);
+    }
+}
+
+// -Ressource - last windowed geometry and fullscreen flag, restored on start
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    pub position: Option<(i32, i32)>, // top left, None lets the window manager pick
+    pub fullscreen: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: VIRTUAL_WIDTH,
+            height: VIRTUAL_HEIGHT,
+            position: None,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowSettings {
+    pub fn load() -> Self {
+        platform::load(WINDOW_SETTINGS_KEY)
+            .and_then(|saved| ron::from_str(&saved).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        match ron::to_string(self) {
+            Ok(saved) => platform::save(WINDOW_SETTINGS_KEY, &saved),
+            Err(err) => warn!("could not serialize window settings: {}", err),
+        }
+    }
+
+    pub fn descriptor(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            width: self.width,
+            height: self.height,
+            position: match self.position {
+                Some((x, y)) => WindowPosition::At(Vec2::new(x as f32, y as f32)),
+                None => WindowPosition::Automatic,
+            },
+            mode: if self.fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            },
+            ..Default::default()
+        }
+    }
+}
+
+// largest virtual sized viewport that fits, centered
+fn canvas_resize_system(
+    windows: Res<Windows>,
+    win_size: Res<WinSize>,
+    mut arena: ResMut<ArenaBounds>,
+    mut resize_events: EventReader<WindowResized>,
+    mut scale_events: EventReader<WindowScaleFactorChanged>,
+    mut camera_query: Query<&mut Camera, With<Camera2d>>,
+    mut ready: Local<bool>,
+) {
+    let primary = WindowId::primary();
+    let resized = resize_events
+        .iter()
+        .filter(|event| event.id == primary)
+        .count()
+        + scale_events
+            .iter()
+            .filter(|event| event.id == primary)
+            .count();
+    if *ready && resized == 0 {
+        return;
+    }
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    if window_size.x == 0 || window_size.y == 0 {
+        return; // minimized
+    }
+
+    let scale = (window_size.x as f32 / win_size.w).min(window_size.y as f32 / win_size.h);
+    let size = (Vec2::new(win_size.w, win_size.h) * scale)
+        .round()
+        .as_uvec2()
+        .min(window_size);
+    for mut camera in camera_query.iter_mut() {
+        camera.viewport = Some(Viewport {
+            physical_position: (window_size - size) / 2,
+            physical_size: size,
+            ..Default::default()
+        });
+        *ready = true;
+    }
+    *arena = ArenaBounds::from_win_size(&win_size);
+}
+
+fn fullscreen_toggle_system(kb: Res<Input<KeyCode>>, mut windows: ResMut<Windows>) {
+    if !kb.just_pressed(KeyCode::F11) {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_mode(match window.mode() {
+            WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+            _ => WindowMode::Windowed,
+        });
+    }
+}
+
+// keeps the settings in sync with the window, saves once things calm down
+fn window_settings_system(
+    windows: Res<Windows>,
+    time: Res<Time>,
+    mut settings: ResMut<WindowSettings>,
+    mut resize_events: EventReader<WindowResized>,
+    mut moved_events: EventReader<WindowMoved>,
+    mut save_at: Local<Option<f64>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let primary = WindowId::primary();
+
+    let mut current = settings.clone();
+    current.fullscreen = window.mode() != WindowMode::Windowed;
+    // only remember the windowed geometry, fullscreen would overwrite it
+    for event in resize_events.iter().filter(|event| event.id == primary) {
+        if !current.fullscreen {
+            current.width = event.width;
+            current.height = event.height;
+        }
+    }
+    for event in moved_events.iter().filter(|event| event.id == primary) {
+        if !current.fullscreen {
+            current.position = Some((event.position.x, event.position.y));
+        }
+    }
+
+    let now = time.seconds_since_startup();
+    if current != *settings {
+        *settings = current;
+        *save_at = Some(now + WINDOW_SAVE_DELAY);
+    }
+    if matches!(*save_at, Some(at) if now >= at) {
+        settings.save();
+        *save_at = None;
+    }
+}
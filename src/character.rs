@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::Hitbox,
+    controls::PlayerInputs,
+    settings::Settings,
+    ui::{despawn_screen, label, screen_root},
+    AppState, GameFonts, GameMode, GameTextures, BOMB_COOLDOWN, CHARACTERS, DASH_COOLDOWN,
+    MAX_PLAYERS,
+};
+
+const CARD_IMAGE_HEIGHT: f32 = 120.;
+
+// -- ships to pick from before every game
+
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SelectedCharacters::default())
+            .add_system_set(
+                SystemSet::on_enter(AppState::CharacterSelect)
+                    .with_system(character_select_setup_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::CharacterSelect)
+                    .with_system(character_select_input_system)
+                    .with_system(character_card_system.after(character_select_input_system))
+                    .with_system(character_join_text_system.after(character_select_input_system)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::CharacterSelect)
+                    .with_system(despawn_screen::<CharacterSelectUi>),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShipSprite {
+    Main,
+    Alt,
+}
+
+impl ShipSprite {
+    pub fn texture(&self, textures: &GameTextures) -> Handle<Image> {
+        match self {
+            ShipSprite::Main => textures.player.clone(),
+            ShipSprite::Alt => textures.player_alt.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PrimaryWeapon {
+    Twin,   // two parallel lasers
+    Spread, // three lasers in a fan
+}
+
+impl PrimaryWeapon {
+    fn name(&self) -> &'static str {
+        match self {
+            PrimaryWeapon::Twin => "TWIN",
+            PrimaryWeapon::Spread => "SPREAD",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Special {
+    Bomb, // clears every enemy laser
+    Dash, // short burst of speed
+}
+
+impl Special {
+    /// seconds until it can be used again
+    pub fn cooldown(&self) -> f64 {
+        match self {
+            Special::Bomb => BOMB_COOLDOWN,
+            Special::Dash => DASH_COOLDOWN,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Special::Bomb => "BOMB",
+            Special::Dash => "DASH",
+        }
+    }
+}
+
+// -- Component - ship definition, the player entity carries the one it was spawned as
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PlayerCharacter {
+    pub name: &'static str,
+    pub key: &'static str, // hitbox override in the config
+    pub sprite: ShipSprite,
+    pub hitbox: Hitbox,
+    pub speed: f32, // times base_speed
+    pub weapon: PrimaryWeapon,
+    pub special: Special,
+    pub lives: u32, // on normal difficulty
+}
+
+// -- Component - when the special is ready again (seconds since startup)
+#[derive(Component, Default)]
+pub struct SpecialState {
+    pub ready_at: f64,
+    pub dash_until: f64,
+}
+
+// -Ressource - index into CHARACTERS per PlayerId, None sits out. kept between games
+pub struct SelectedCharacters(pub [Option<usize>; MAX_PLAYERS]);
+
+impl Default for SelectedCharacters {
+    fn default() -> Self {
+        let mut selected = [None; MAX_PLAYERS];
+        selected[0] = Some(0); // player one always plays
+        Self(selected)
+    }
+}
+
+impl SelectedCharacters {
+    pub fn character(&self, player: usize) -> Option<&'static PlayerCharacter> {
+        self.0[player].map(|index| &CHARACTERS[index])
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().flatten().count()
+    }
+}
+
+// region: --- Components
+#[derive(Component)]
+struct CharacterSelectUi;
+
+#[derive(Component)]
+struct CharacterCard(usize);
+
+// P1/P2 tags under a card
+#[derive(Component)]
+struct CharacterCardTags(usize);
+
+#[derive(Component)]
+struct JoinText;
+// endregion: --- Components
+
+fn character_select_setup_system(
+    mut commands: Commands,
+    fonts: Res<GameFonts>,
+    settings: Res<Settings>,
+    textures: Res<GameTextures>,
+    images: Res<Assets<Image>>,
+) {
+    let lang = settings.language;
+    commands
+        .spawn_bundle(screen_root())
+        .insert(CharacterSelectUi)
+        .with_children(|parent| {
+            parent.spawn_bundle(label(
+                &fonts,
+                lang.tr("SELECT YOUR SHIP"),
+                48.,
+                Color::WHITE,
+            ));
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for (index, character) in CHARACTERS.iter().enumerate() {
+                        // keep the aspect of the sprite
+                        let texture = character.sprite.texture(&textures);
+                        let size = images.get(&texture).map_or(Vec2::ONE, |image| image.size());
+                        let width = CARD_IMAGE_HEIGHT * size.x / size.y;
+                        let stats = [
+                            format!("{} {:.0}%", lang.tr("SPEED"), character.speed * 100.),
+                            format!(
+                                "{} {}",
+                                lang.tr("LIVES"),
+                                settings.difficulty.lives(character.lives)
+                            ),
+                            format!("{} {}", lang.tr("WEAPON"), lang.tr(character.weapon.name())),
+                            format!(
+                                "{} {}",
+                                lang.tr("SPECIAL"),
+                                lang.tr(character.special.name())
+                            ),
+                        ];
+
+                        parent
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::ColumnReverse,
+                                    align_items: AlignItems::Center,
+                                    padding: UiRect::all(Val::Px(16.)),
+                                    margin: UiRect::all(Val::Px(12.)),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            })
+                            .insert(CharacterCard(index))
+                            .with_children(|parent| {
+                                parent.spawn_bundle(ImageBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(width), Val::Px(CARD_IMAGE_HEIGHT)),
+                                        margin: UiRect::all(Val::Px(6.)),
+                                        ..Default::default()
+                                    },
+                                    image: texture.into(),
+                                    ..Default::default()
+                                });
+                                parent.spawn_bundle(label(
+                                    &fonts,
+                                    character.name,
+                                    28.,
+                                    Color::WHITE,
+                                ));
+                                for line in stats {
+                                    parent.spawn_bundle(label(&fonts, line, 18., Color::GRAY));
+                                }
+                                parent
+                                    .spawn_bundle(label(&fonts, "", 22., Color::WHITE))
+                                    .insert(CharacterCardTags(index));
+                            });
+                    }
+                });
+            parent.spawn_bundle(label(
+                &fonts,
+                lang.tr("LEFT/RIGHT choose   ENTER start   SHIFT special   CTRL missiles"),
+                18.,
+                Color::GRAY,
+            ));
+            parent
+                .spawn_bundle(label(&fonts, "", 18., Color::rgb(0.6, 0.9, 1.)))
+                .insert(JoinText);
+        });
+}
+
+// everyone picks with their own left/right, P2 joins with fire and leaves with special
+fn character_select_input_system(
+    mut kb: ResMut<Input<KeyCode>>,
+    inputs: Res<PlayerInputs>,
+    mut selected: ResMut<SelectedCharacters>,
+    mut mode: ResMut<GameMode>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let count = CHARACTERS.len();
+    for (player, input) in inputs.0.iter().enumerate() {
+        match selected.0[player] {
+            Some(_) if player > 0 && input.special_pressed => selected.0[player] = None,
+            Some(index) if input.step != 0 => {
+                selected.0[player] =
+                    Some((index as i32 + input.step).rem_euclid(count as i32) as usize);
+            }
+            // starts on the ship after player one's
+            None if input.fire_pressed => {
+                selected.0[player] = Some(selected.0[0].map_or(0, |first| (first + 1) % count));
+            }
+            _ => {}
+        }
+    }
+    if kb.clear_just_pressed(KeyCode::Return) {
+        *mode = if selected.count() > 1 {
+            GameMode::CoOp
+        } else {
+            GameMode::Arcade
+        };
+        let _ = state.set(AppState::Playing);
+    }
+}
+
+fn character_card_system(
+    selected: Res<SelectedCharacters>,
+    mut query: Query<(&CharacterCard, &mut UiColor)>,
+    mut tags_query: Query<(&CharacterCardTags, &mut Text)>,
+) {
+    let picked_by = |card: usize| {
+        (0..MAX_PLAYERS)
+            .filter(|player| selected.0[*player] == Some(card))
+            .collect::<Vec<_>>()
+    };
+    for (card, mut color) in query.iter_mut() {
+        let wanted = match picked_by(card.0).first() {
+            Some(0) => Color::rgba(1., 0.85, 0.3, 0.25),
+            Some(_) => Color::rgba(0.6, 0.9, 1., 0.25),
+            None => Color::rgba(1., 1., 1., 0.05),
+        };
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+    // only worth a tag once there are two players
+    let coop = selected.count() > 1;
+    for (tags, mut text) in tags_query.iter_mut() {
+        let wanted = if coop {
+            picked_by(tags.0)
+                .iter()
+                .map(|player| format!("P{}", player + 1))
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            String::new()
+        };
+        if text.sections[0].value != wanted {
+            text.sections[0].value = wanted;
+        }
+    }
+}
+
+fn character_join_text_system(
+    selected: Res<SelectedCharacters>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Text, With<JoinText>>,
+) {
+    let lang = settings.language;
+    let wanted = if selected.0[1].is_some() {
+        lang.tr("P2 G to leave")
+    } else {
+        lang.tr("P2 press F or pad A to join")
+    };
+    for mut text in query.iter_mut() {
+        if text.sections[0].value != wanted {
+            text.sections[0].value = wanted.to_string();
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::{prelude::*, render::render_resource::TextureFormat, utils::HashMap};
+
+// alpha at or above this counts as solid
+const ALPHA_THRESHOLD: u8 = 128;
+
+// -- Pixel mask built from an image alpha channel, row 0 is the bottom row
+pub struct AlphaMask {
+    width: u32,
+    height: u32,
+    solid: Vec<bool>,
+}
+
+impl AlphaMask {
+    pub fn from_image(image: &Image) -> Option<Self> {
+        // png loads as rgba8, anything else (hdr, ...) has no usable mask
+        match image.texture_descriptor.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {}
+            _ => return None,
+        }
+
+        let size = image.texture_descriptor.size;
+        let (width, height) = (size.width, size.height);
+        let mut solid = Vec::with_capacity((width * height) as usize);
+        for y in (0..height).rev() {
+            for x in 0..width {
+                let alpha = image.data[((y * width + x) * 4 + 3) as usize];
+                solid.push(alpha >= ALPHA_THRESHOLD);
+            }
+        }
+
+        Some(Self {
+            width,
+            height,
+            solid,
+        })
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32)
+    }
+
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.solid[(y * self.width + x) as usize]
+    }
+}
+
+// -Ressource - masks per loaded image
+#[derive(Default)]
+pub struct HitMasks(pub HashMap<Handle<Image>, AlphaMask>);
+
+pub fn hit_mask_build_system(
+    mut image_events: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    mut hit_masks: ResMut<HitMasks>,
+) {
+    for event in image_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if let Some(mask) = images.get(handle).and_then(AlphaMask::from_image) {
+                    hit_masks.0.insert(handle.clone_weak(), mask);
+                }
+            }
+            AssetEvent::Removed { handle } => {
+                hit_masks.0.remove(handle);
+            }
+        }
+    }
+}
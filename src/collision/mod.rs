@@ -0,0 +1,288 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+
+use crate::{
+    components::{Collider, Hitbox, HitboxShape, SpriteSize, Swept},
+    pool::Pooled,
+    AppState,
+};
+
+use self::{
+    mask::{hit_mask_build_system, HitMasks},
+    shape::{overlaps, WorldShape},
+    size::sprite_size_system,
+};
+
+mod mask;
+mod shape;
+mod size;
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HitMasks::default())
+            .add_event::<CollisionEvent>()
+            .add_system(hit_mask_build_system)
+            .add_system(sprite_size_system.before(collision_detection_system))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing).with_system(collision_detection_system),
+            );
+    }
+}
+
+// -- Event - two colliders overlapping this frame (a/b order is not meaningful)
+pub struct CollisionEvent {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+impl CollisionEvent {
+    /// returns the pair ordered as (entity matching `is_first`, other)
+    pub fn ordered(&self, is_first: impl Fn(Entity) -> bool) -> Option<(Entity, Entity)> {
+        if is_first(self.a) {
+            Some((self.a, self.b))
+        } else if is_first(self.b) {
+            Some((self.b, self.a))
+        } else {
+            None
+        }
+    }
+}
+
+type ColliderQuery<'a> = (
+    Entity,
+    &'a Transform,
+    &'a SpriteSize,
+    &'a Collider,
+    Option<&'a Hitbox>,
+    Option<&'a Handle<Image>>,
+    Option<&'a Swept>,
+);
+
+pub fn collision_detection_system(
+    mut collision_events: EventWriter<CollisionEvent>,
+    hit_masks: Res<HitMasks>,
+    query: Query<ColliderQuery, Without<Pooled>>,
+) {
+    // snapshot once, then test every pair only once
+    // (non swept colliders use their current position as previous one)
+    let colliders: Vec<(Entity, WorldShape, Vec2, &Collider, bool)> = query
+        .iter()
+        .map(|(entity, tf, size, collider, hitbox, image, swept)| {
+            let shape = world_shape(tf, size, hitbox, image, &hit_masks);
+            let moved = swept.map_or(Vec2::ZERO, |swept| (tf.translation - swept.prev).xy());
+            (entity, shape, moved, collider, swept.is_some())
+        })
+        .collect();
+
+    for (i, (a_entity, a_shape, a_moved, a_collider, a_swept)) in colliders.iter().enumerate() {
+        for (b_entity, b_shape, b_moved, b_collider, b_swept) in colliders.iter().skip(i + 1) {
+            if !a_collider.interacts_with(b_collider) {
+                continue;
+            }
+
+            let hit = if *a_swept || *b_swept {
+                // move a relative to b
+                swept_overlaps(a_shape, *a_moved - *b_moved, b_shape)
+            } else {
+                overlaps(a_shape, b_shape)
+            };
+
+            if hit {
+                collision_events.send(CollisionEvent {
+                    a: *a_entity,
+                    b: *b_entity,
+                });
+            }
+        }
+    }
+}
+
+fn world_shape<'a>(
+    tf: &Transform,
+    size: &SpriteSize,
+    hitbox: Option<&Hitbox>,
+    image: Option<&Handle<Image>>,
+    hit_masks: &'a HitMasks,
+) -> WorldShape<'a> {
+    let (shape, offset, hitbox_scale) = hitbox
+        .map_or((HitboxShape::Rect, Vec2::ZERO, 1.), |hitbox| {
+            (hitbox.shape, hitbox.offset, hitbox.scale)
+        });
+    let scale = tf.scale.xy() * hitbox_scale;
+    let center = tf.translation.xy() + offset * tf.scale.xy();
+    let rect = WorldShape::Aabb {
+        center,
+        half: (size.0 * scale / 2.).abs(),
+    };
+
+    match shape {
+        HitboxShape::Rect => rect,
+        HitboxShape::Circle { radius } => WorldShape::Circle {
+            center,
+            radius: radius * scale.x.abs(),
+        },
+        HitboxShape::Capsule {
+            half_length,
+            radius,
+        } => {
+            let half = Vec2::new(0., half_length * scale.y.abs());
+            WorldShape::Capsule {
+                a: center - half,
+                b: center + half,
+                radius: radius * scale.x.abs(),
+            }
+        }
+        // until the image (and its mask) is loaded fall back to the rect
+        HitboxShape::Mask => match image.and_then(|image| hit_masks.0.get(image)) {
+            Some(mask) => WorldShape::Mask {
+                center,
+                pixel: scale,
+                mask,
+            },
+            None => rect,
+        },
+    }
+}
+
+// a moved by `moved` this frame, b is static
+fn swept_overlaps(a: &WorldShape, moved: Vec2, b: &WorldShape) -> bool {
+    let end = a.center();
+    let start = end - moved;
+    let half_size = a.half_extents() + b.half_extents();
+    if !segment_hits_aabb(start, end, b.center(), half_size) {
+        return false;
+    }
+
+    if let (WorldShape::Aabb { .. }, WorldShape::Aabb { .. }) = (a, b) {
+        return true;
+    }
+
+    // sample the path at steps no longer than the smaller shape
+    let step = a
+        .half_extents()
+        .min_element()
+        .min(b.half_extents().min_element())
+        .max(1.);
+    let steps = (moved.length() / step).ceil().max(1.) as u32;
+    (0..=steps).any(|i| {
+        let back = moved * (1. - i as f32 / steps as f32);
+        overlaps(&a.translated(-back), b)
+    })
+}
+
+/// slab test of the segment start..end against the box at `center`
+pub fn segment_hits_aabb(start: Vec2, end: Vec2, center: Vec2, half_size: Vec2) -> bool {
+    let min = center - half_size;
+    let max = center + half_size;
+    let delta = end - start;
+
+    let mut t_min: f32 = 0.;
+    let mut t_max: f32 = 1.;
+
+    for axis in 0..2 {
+        if delta[axis].abs() < f32::EPSILON {
+            // parallel to this slab, must already be inside it
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return false;
+            }
+        } else {
+            let inv = 1. / delta[axis];
+            let t1 = (min[axis] - start[axis]) * inv;
+            let t2 = (max[axis] - start[axis]) * inv;
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CENTER: Vec2 = Vec2::new(0., 100.);
+    const HALF: Vec2 = Vec2::new(10., 10.);
+
+    #[test]
+    fn segment_through_box_hits() {
+        // both ends outside, one frame jumps over the whole box
+        assert!(segment_hits_aabb(
+            Vec2::new(0., 50.),
+            Vec2::new(0., 150.),
+            CENTER,
+            HALF
+        ));
+        // diagonal through a corner
+        assert!(segment_hits_aabb(
+            Vec2::new(-20., 80.),
+            Vec2::new(20., 120.),
+            CENTER,
+            HALF
+        ));
+    }
+
+    #[test]
+    fn segment_short_of_box_misses() {
+        assert!(!segment_hits_aabb(
+            Vec2::new(0., 50.),
+            Vec2::new(0., 85.),
+            CENTER,
+            HALF
+        ));
+        // passes beside the corner
+        assert!(!segment_hits_aabb(
+            Vec2::new(-30., 80.),
+            Vec2::new(0., 130.),
+            CENTER,
+            HALF
+        ));
+    }
+
+    #[test]
+    fn parallel_segment_outside_slab_misses() {
+        // vertical, next to the box
+        assert!(!segment_hits_aabb(
+            Vec2::new(15., 50.),
+            Vec2::new(15., 150.),
+            CENTER,
+            HALF
+        ));
+        // horizontal, above the box
+        assert!(!segment_hits_aabb(
+            Vec2::new(-50., 115.),
+            Vec2::new(50., 115.),
+            CENTER,
+            HALF
+        ));
+    }
+
+    #[test]
+    fn parallel_segment_inside_slab_hits() {
+        assert!(segment_hits_aabb(
+            Vec2::new(-50., 105.),
+            Vec2::new(50., 105.),
+            CENTER,
+            HALF
+        ));
+        // edge counts as a hit
+        assert!(segment_hits_aabb(
+            Vec2::new(10., 50.),
+            Vec2::new(10., 150.),
+            CENTER,
+            HALF
+        ));
+    }
+
+    #[test]
+    fn resting_point_inside_hits() {
+        let point = Vec2::new(3., 97.);
+        assert!(segment_hits_aabb(point, point, CENTER, HALF));
+        let point = Vec2::new(30., 97.);
+        assert!(!segment_hits_aabb(point, point, CENTER, HALF));
+    }
+}
@@ -0,0 +1,363 @@
+use bevy::prelude::*;
+
+use super::mask::AlphaMask;
+
+// -- Shape resolved to world space for a single frame
+pub enum WorldShape<'a> {
+    Aabb {
+        center: Vec2,
+        half: Vec2,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    // segment a..b grown by radius
+    Capsule {
+        a: Vec2,
+        b: Vec2,
+        radius: f32,
+    },
+    // one mask pixel covers `pixel` world units
+    Mask {
+        center: Vec2,
+        pixel: Vec2,
+        mask: &'a AlphaMask,
+    },
+}
+
+impl<'a> WorldShape<'a> {
+    pub fn center(&self) -> Vec2 {
+        match self {
+            WorldShape::Aabb { center, .. }
+            | WorldShape::Circle { center, .. }
+            | WorldShape::Mask { center, .. } => *center,
+            WorldShape::Capsule { a, b, .. } => (*a + *b) / 2.,
+        }
+    }
+
+    /// half size of the bounding box around center()
+    pub fn half_extents(&self) -> Vec2 {
+        match self {
+            WorldShape::Aabb { half, .. } => *half,
+            WorldShape::Circle { radius, .. } => Vec2::splat(*radius),
+            WorldShape::Capsule { a, b, radius } => (*a - *b).abs() / 2. + Vec2::splat(*radius),
+            WorldShape::Mask { pixel, mask, .. } => mask.size() * pixel.abs() / 2.,
+        }
+    }
+
+    pub fn translated(&self, delta: Vec2) -> WorldShape<'a> {
+        match self {
+            WorldShape::Aabb { center, half } => WorldShape::Aabb {
+                center: *center + delta,
+                half: *half,
+            },
+            WorldShape::Circle { center, radius } => WorldShape::Circle {
+                center: *center + delta,
+                radius: *radius,
+            },
+            WorldShape::Capsule { a, b, radius } => WorldShape::Capsule {
+                a: *a + delta,
+                b: *b + delta,
+                radius: *radius,
+            },
+            WorldShape::Mask {
+                center,
+                pixel,
+                mask,
+            } => WorldShape::Mask {
+                center: *center + delta,
+                pixel: *pixel,
+                mask,
+            },
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            WorldShape::Aabb { center, half } => {
+                let d = (point - *center).abs();
+                d.x <= half.x && d.y <= half.y
+            }
+            WorldShape::Circle { center, radius } => {
+                point.distance_squared(*center) <= radius * radius
+            }
+            WorldShape::Capsule { a, b, radius } => {
+                point_segment_distance(point, *a, *b) <= *radius
+            }
+            WorldShape::Mask {
+                center,
+                pixel,
+                mask,
+            } => {
+                let local = (point - *center) / pixel.abs() + mask.size() / 2.;
+                local.x >= 0. && local.y >= 0. && mask.is_solid(local.x as u32, local.y as u32)
+            }
+        }
+    }
+
+    fn bounds_overlap(&self, other: &WorldShape) -> bool {
+        let d = (self.center() - other.center()).abs();
+        let h = self.half_extents() + other.half_extents();
+        d.x <= h.x && d.y <= h.y
+    }
+}
+
+pub fn overlaps(a: &WorldShape, b: &WorldShape) -> bool {
+    use WorldShape::*;
+
+    if !a.bounds_overlap(b) {
+        return false;
+    }
+
+    match (a, b) {
+        // bounds already overlap
+        (Aabb { .. }, Aabb { .. }) => true,
+        (Circle { center, radius }, Aabb { center: bc, half })
+        | (Aabb { center: bc, half }, Circle { center, radius }) => {
+            let closest = center.clamp(*bc - *half, *bc + *half);
+            closest.distance_squared(*center) <= radius * radius
+        }
+        (
+            Circle {
+                center: ca,
+                radius: ra,
+            },
+            Circle {
+                center: cb,
+                radius: rb,
+            },
+        ) => ca.distance_squared(*cb) <= (ra + rb) * (ra + rb),
+        (Capsule { a, b, radius }, Circle { center, radius: rc })
+        | (Circle { center, radius: rc }, Capsule { a, b, radius }) => {
+            point_segment_distance(*center, *a, *b) <= radius + rc
+        }
+        (Capsule { a, b, radius }, Aabb { center, half })
+        | (Aabb { center, half }, Capsule { a, b, radius }) => {
+            segment_aabb_distance(*a, *b, *center, *half) <= *radius
+        }
+        (
+            Capsule {
+                a: a1,
+                b: b1,
+                radius: r1,
+            },
+            Capsule {
+                a: a2,
+                b: b2,
+                radius: r2,
+            },
+        ) => segment_segment_distance(*a1, *b1, *a2, *b2) <= r1 + r2,
+        (Mask { .. }, other) => mask_overlaps(a, other),
+        (other, Mask { .. }) => mask_overlaps(b, other),
+    }
+}
+
+// test every solid mask pixel inside the shared bounds against the other shape
+fn mask_overlaps(mask_shape: &WorldShape, other: &WorldShape) -> bool {
+    let (center, pixel, mask) = match mask_shape {
+        WorldShape::Mask {
+            center,
+            pixel,
+            mask,
+        } => (*center, pixel.abs(), *mask),
+        _ => return false,
+    };
+
+    let half = mask.size() / 2.;
+    let other_min = other.center() - other.half_extents();
+    let other_max = other.center() + other.half_extents();
+    // shared bounds in mask pixel space
+    let min = ((other_min - center) / pixel + half).max(Vec2::ZERO);
+    let max = ((other_max - center) / pixel + half).min(mask.size());
+
+    for py in (min.y as u32)..(max.y.ceil() as u32) {
+        for px in (min.x as u32)..(max.x.ceil() as u32) {
+            if !mask.is_solid(px, py) {
+                continue;
+            }
+            let point = center + (Vec2::new(px as f32, py as f32) + 0.5 - half) * pixel;
+            if other.contains(point) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+pub fn point_segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    let t = if len_sq > 0. {
+        ((point - a).dot(ab) / len_sq).clamp(0., 1.)
+    } else {
+        0.
+    };
+    point.distance(a + ab * t)
+}
+
+fn segments_intersect(a1: Vec2, b1: Vec2, a2: Vec2, b2: Vec2) -> bool {
+    let cross = |o: Vec2, p: Vec2, q: Vec2| (p - o).perp_dot(q - o);
+    let d1 = cross(a2, b2, a1);
+    let d2 = cross(a2, b2, b1);
+    let d3 = cross(a1, b1, a2);
+    let d4 = cross(a1, b1, b2);
+    d1 * d2 < 0. && d3 * d4 < 0.
+}
+
+fn segment_segment_distance(a1: Vec2, b1: Vec2, a2: Vec2, b2: Vec2) -> f32 {
+    if segments_intersect(a1, b1, a2, b2) {
+        return 0.;
+    }
+    point_segment_distance(a1, a2, b2)
+        .min(point_segment_distance(b1, a2, b2))
+        .min(point_segment_distance(a2, a1, b1))
+        .min(point_segment_distance(b2, a1, b1))
+}
+
+fn segment_aabb_distance(a: Vec2, b: Vec2, center: Vec2, half: Vec2) -> f32 {
+    if super::segment_hits_aabb(a, b, center, half) {
+        return 0.;
+    }
+    // closest points are on a segment end or a box corner
+    let (min, max) = (center - half, center + half);
+    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+    let to_box = |p: Vec2| p.distance(p.clamp(min, max));
+
+    corners
+        .iter()
+        .map(|corner| point_segment_distance(*corner, a, b))
+        .fold(to_box(a).min(to_box(b)), f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    fn circle(x: f32, y: f32, radius: f32) -> WorldShape<'static> {
+        WorldShape::Circle {
+            center: Vec2::new(x, y),
+            radius,
+        }
+    }
+
+    fn capsule(a: (f32, f32), b: (f32, f32), radius: f32) -> WorldShape<'static> {
+        WorldShape::Capsule {
+            a: Vec2::new(a.0, a.1),
+            b: Vec2::new(b.0, b.1),
+            radius,
+        }
+    }
+
+    fn square(half: f32) -> WorldShape<'static> {
+        WorldShape::Aabb {
+            center: Vec2::ZERO,
+            half: Vec2::splat(half),
+        }
+    }
+
+    #[test]
+    fn circle_circle() {
+        assert!(overlaps(&circle(0., 0., 5.), &circle(8., 0., 3.)));
+        assert!(!overlaps(&circle(0., 0., 5.), &circle(6., 6., 3.)));
+    }
+
+    #[test]
+    fn circle_aabb_corner() {
+        // bounds overlap in both, only the distance to the corner decides
+        assert!(overlaps(&square(10.), &circle(13., 13., 5.)));
+        assert!(!overlaps(&square(10.), &circle(14., 14., 5.)));
+        assert!(overlaps(&circle(0., 14., 5.), &square(10.)));
+    }
+
+    #[test]
+    fn capsule_capsule_side_by_side() {
+        let left = capsule((0., -10.), (0., 10.), 2.);
+        assert!(!overlaps(&left, &capsule((5., -10.), (5., 10.), 2.)));
+        assert!(overlaps(&left, &capsule((5., -10.), (5., 10.), 3.)));
+    }
+
+    #[test]
+    fn capsule_capsule_crossing() {
+        let horizontal = capsule((-10., 0.), (10., 0.), 0.5);
+        assert!(overlaps(&horizontal, &capsule((0., -10.), (0., 10.), 0.5)));
+        assert_eq!(
+            segment_segment_distance(
+                Vec2::new(-10., 0.),
+                Vec2::new(10., 0.),
+                Vec2::new(0., -10.),
+                Vec2::new(0., 10.)
+            ),
+            0.
+        );
+    }
+
+    #[test]
+    fn capsule_endpoints() {
+        // end to end, 3 apart
+        let lower = capsule((0., 0.), (0., 10.), 1.5);
+        assert!(overlaps(&lower, &capsule((0., 13.), (0., 20.), 1.5)));
+        assert!(!overlaps(&lower, &capsule((0., 13.), (0., 20.), 1.4)));
+
+        // round cap, the circle is inside the capsule bounds but past the cap
+        let upright = capsule((0., 0.), (0., 10.), 2.);
+        assert!(overlaps(&upright, &circle(2., 12., 1.)));
+        assert!(!overlaps(&upright, &circle(2.5, 12.5, 1.)));
+    }
+
+    #[test]
+    fn capsule_aabb() {
+        // diagonal past the corner, closest point is 4.95 from it
+        assert!(!overlaps(
+            &capsule((12., 15.), (15., 12.), 2.),
+            &square(10.)
+        ));
+        assert!(overlaps(&capsule((12., 15.), (15., 12.), 5.), &square(10.)));
+        // straight through, thin
+        assert!(overlaps(&square(10.), &capsule((-20., 0.), (20., 0.), 0.1)));
+        assert_eq!(
+            segment_aabb_distance(
+                Vec2::new(0., 12.),
+                Vec2::new(0., 20.),
+                Vec2::ZERO,
+                Vec2::splat(10.)
+            ),
+            2.
+        );
+    }
+
+    #[test]
+    fn mask_transparent_corner() {
+        // 4x4, the top right 2x2 of the image is see-through
+        let mut data = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                let alpha = if y < 2 && x >= 2 { 0 } else { 255 };
+                data.extend_from_slice(&[255, 255, 255, alpha]);
+            }
+        }
+        let image = Image::new(
+            Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8Unorm,
+        );
+        let mask = AlphaMask::from_image(&image).unwrap();
+        let shape = WorldShape::Mask {
+            center: Vec2::ZERO,
+            pixel: Vec2::ONE,
+            mask: &mask,
+        };
+
+        // inside the bounds either way, only the solid pixels count
+        assert!(!overlaps(&shape, &circle(1., 1., 0.8)));
+        assert!(overlaps(&shape, &circle(-1., 1., 0.8)));
+        assert!(overlaps(&circle(1., -1., 0.8), &shape));
+    }
+}
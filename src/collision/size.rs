@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+use crate::components::{Collider, SpriteSize};
+
+// colliders get their SpriteSize from the image, until it is loaded they don't collide
+pub fn sprite_size_system(
+    mut commands: Commands,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    unsized_query: Query<(Entity, &Handle<Image>), (With<Collider>, Without<SpriteSize>)>,
+    mut sized_query: Query<(&Handle<Image>, &mut SpriteSize)>,
+) {
+    for (entity, handle) in unsized_query.iter() {
+        if let Some(image) = images.get(handle) {
+            commands.entity(entity).insert(SpriteSize(image.size()));
+        }
+    }
+
+    // swapped art keeps colliding with its new size
+    for event in image_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            let size = match images.get(handle) {
+                Some(image) => image.size(),
+                None => continue,
+            };
+            for (image, mut sprite_size) in sized_query.iter_mut() {
+                if image == handle {
+                    sprite_size.0 = size;
+                }
+            }
+        }
+    }
+}
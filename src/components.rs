@@ -1,7 +1,9 @@
 use bevy::{
-    prelude::{Component, Vec2, Vec3},
+    prelude::{Component, Entity, Vec2, Vec3},
     time::Timer,
+    utils::HashSet,
 };
+use serde::{Deserialize, Serialize};
 
 // region --- Common Components
 #[derive(Component)]
@@ -10,14 +12,33 @@ pub struct Velocity {
     pub y: f32,
 }
 
+impl Velocity {
+    pub fn length(&self) -> f32 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+}
+
 #[derive(Component)]
-pub struct Movable {
-    pub auto_despawn: bool,
+pub struct Movable;
+
+// when an entity leaving the arena gets removed (no policy = never)
+#[derive(Component, Clone, Copy)]
+pub enum DespawnPolicy {
+    // as soon as it is `margin` outside the arena (projectiles, pickups)
+    OffScreen { margin: f32 },
+    // spawned outside, only removed after it entered once (enemies that flee)
+    // or once it is ENEMY_LOST_MARGIN out without ever entering
+    AfterEntering { margin: f32, entered: bool },
 }
 
 #[derive(Component)]
 pub struct Laser;
 
+// entity that fired the projectile
+#[derive(Component, Clone, Copy)]
+pub struct Owner(pub Entity);
+
+// filled in from the sprite image once it is loaded, see collision::size
 #[derive(Component)]
 pub struct SpriteSize(pub Vec2);
 
@@ -26,15 +47,107 @@ impl From<(f32, f32)> for SpriteSize {
         SpriteSize(Vec2::new(val.0, val.1))
     }
 }
+
+// layer = what this collider is, mask = which layers it reports collisions with
+#[derive(Component, Clone, Copy)]
+pub struct Collider {
+    pub layer: u32,
+    pub mask: u32,
+}
+
+impl Collider {
+    pub const PLAYER: u32 = 1 << 0;
+    pub const PLAYER_LASER: u32 = 1 << 1;
+    pub const ENEMY: u32 = 1 << 2;
+    pub const ENEMY_LASER: u32 = 1 << 3;
+    pub const POWER_UP: u32 = 1 << 4;
+
+    pub fn player() -> Self {
+        Self {
+            layer: Self::PLAYER,
+            mask: Self::ENEMY_LASER,
+        }
+    }
+    pub fn player_laser() -> Self {
+        Self {
+            layer: Self::PLAYER_LASER,
+            mask: Self::ENEMY,
+        }
+    }
+    pub fn enemy() -> Self {
+        Self {
+            layer: Self::ENEMY,
+            mask: Self::PLAYER_LASER,
+        }
+    }
+    pub fn enemy_laser() -> Self {
+        Self {
+            layer: Self::ENEMY_LASER,
+            mask: Self::PLAYER,
+        }
+    }
+
+    pub fn power_up() -> Self {
+        Self {
+            layer: Self::POWER_UP,
+            mask: Self::PLAYER,
+        }
+    }
+
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask & other.layer != 0 || other.mask & self.layer != 0
+    }
+}
+
+// narrow phase shape, sizes are in sprite pixels before the transform scale
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum HitboxShape {
+    Rect, // full SpriteSize
+    Circle { radius: f32 },
+    Capsule { half_length: f32, radius: f32 }, // vertical
+    Mask,                                      // alpha of the sprite image
+}
+
+// entities without a Hitbox collide with their full SpriteSize rect
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Hitbox {
+    pub shape: HitboxShape,
+    pub offset: Vec2,
+    pub scale: f32,
+}
+
+// previous frame position, lets fast colliders sweep instead of tunneling
+#[derive(Component)]
+pub struct Swept {
+    pub prev: Vec3,
+}
+
+impl From<Vec3> for Swept {
+    fn from(prev: Vec3) -> Self {
+        Swept { prev }
+    }
+}
 // endregion --- Common Components
 
 // region --- Player Components
 #[derive(Component)]
 pub struct Player;
 
+// which player (0 based), on the ship and on everything it fires
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlayerId(pub usize);
+
 #[derive(Component)]
 pub struct FromPlayer;
 
+// player projectiles without it deal 1
+#[derive(Component, Clone, Copy)]
+pub struct Damage(pub u32);
+
+// goes through enemies instead of stopping, each one is hit once
+#[derive(Component, Default)]
+pub struct Piercing(pub HashSet<Entity>);
+
 // endregion --- Player Components
 
 // region --- Enemy Components
@@ -42,6 +155,32 @@ pub struct FromPlayer;
 #[derive(Component)]
 pub struct Enemy;
 
+// kind of enemy, drives scoring and effects
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EnemyArchetype {
+    Grunt,
+}
+
+impl EnemyArchetype {
+    /// name in the config file
+    pub fn key(&self) -> &'static str {
+        match self {
+            EnemyArchetype::Grunt => "grunt",
+        }
+    }
+
+    /// hit points on spawn
+    pub fn health(&self) -> u32 {
+        match self {
+            EnemyArchetype::Grunt => 1,
+        }
+    }
+}
+
+// hit points left, player projectiles take off their Damage
+#[derive(Component)]
+pub struct Health(pub u32);
+
 #[derive(Component)]
 pub struct FromEnemy;
 // endregion --- Enemy Components
@@ -49,16 +188,4 @@ pub struct FromEnemy;
 // region --- Explosion Components
 #[derive(Component)]
 pub struct Explosion;
-
-#[derive(Component)]
-pub struct ExplosionToSpawn(pub Vec3);
-
-#[derive(Component)]
-pub struct ExplosionTimer(pub Timer);
-
-impl Default for ExplosionTimer {
-    fn default() -> Self {
-        Self(Timer::from_seconds(0.05, true))
-    }
-}
 // endregion --- Explosion Components
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{components::Hitbox, loading::LoadingAssets, GAME_CONFIG};
+
+// -- tuning values from a RON asset, edits show up while the game runs
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .insert_resource(GameConfig::default())
+            .add_startup_system(config_load_system)
+            .add_system(config_reload_system);
+    }
+}
+
+// -Ressource - current tuning, also the asset type of the config file.
+// missing fields keep their default, so the file only needs what gets tuned
+#[derive(Serialize, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "5b1f3c1e-8a2d-4f57-9c3e-2e6f0d7a4b91"]
+#[serde(default)]
+pub struct GameConfig {
+    pub base_speed: f32, // px per second at velocity 1
+    pub enemy_max: u32,
+    pub formation_members_max: u32,
+    pub player_respawn_delay: f64, // seconds
+    pub sprite_scale: f32,
+    // replace the built in hitbox of a ship, "player_laser", "enemy_laser" or an enemy archetype
+    pub hitboxes: BTreeMap<String, Hitbox>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            base_speed: 300.,
+            enemy_max: 100,
+            formation_members_max: 10,
+            player_respawn_delay: 1.,
+            sprite_scale: 0.5,
+            hitboxes: BTreeMap::new(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// override from the config file, else the built in one
+    pub fn hitbox(&self, key: &str, default: Hitbox) -> Hitbox {
+        self.hitboxes.get(key).copied().unwrap_or(default)
+    }
+}
+
+// keeps the config asset loaded so it gets reloaded
+struct GameConfigHandle(Handle<GameConfig>);
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config: GameConfig = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+fn config_load_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let handle = loading.load(&asset_server, GAME_CONFIG);
+    commands.insert_resource(GameConfigHandle(handle));
+}
+
+// copies the asset into the resource on load and on every change of the file
+fn config_reload_system(
+    handle: Res<GameConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+    mut events: EventReader<AssetEvent<GameConfig>>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(loaded) = configs.get(changed) {
+                    *config = loaded.clone();
+                    info!("game config loaded");
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{settings::SettingsMenu, MAX_PLAYERS, PLAYER_KEYS, STICK_DEADZONE};
+
+// -- keyboard halves and gamepads turned into one input per player.
+// player n gets the n-th connected gamepad on top of its keys
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayerInputs::default())
+            .add_system_to_stage(CoreStage::PreUpdate, controls_system.after(InputSystem));
+    }
+}
+
+pub struct KeyBindings {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub fire: KeyCode,
+    pub special: KeyCode,
+    pub secondary: KeyCode, // homing missiles
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct PlayerInput {
+    pub x: f32, // -1..1
+    pub fire: bool,
+    pub fire_pressed: bool, // this frame only
+    pub fire_released: bool,
+    pub special_pressed: bool,
+    pub secondary: bool,
+    pub step: i32, // -1/1 on the frame left/right went down, for menus
+}
+
+// -Ressource - this frame's input, indexed by PlayerId
+#[derive(Default)]
+pub struct PlayerInputs(pub [PlayerInput; MAX_PLAYERS]);
+
+pub fn controls_system(
+    kb: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    menu: Res<SettingsMenu>,
+    mut inputs: ResMut<PlayerInputs>,
+) {
+    // held keys stay pressed while the settings menu is open, nobody plays
+    if menu.open {
+        *inputs = PlayerInputs::default();
+        return;
+    }
+    for (id, input) in inputs.0.iter_mut().enumerate() {
+        let keys = &PLAYER_KEYS[id];
+        let mut x = 0.;
+        if kb.pressed(keys.left) {
+            x -= 1.;
+        }
+        if kb.pressed(keys.right) {
+            x += 1.;
+        }
+        let mut step = if kb.just_pressed(keys.left) {
+            -1
+        } else if kb.just_pressed(keys.right) {
+            1
+        } else {
+            0
+        };
+        let mut fire = kb.pressed(keys.fire);
+        let mut fire_pressed = kb.just_pressed(keys.fire);
+        let mut fire_released = kb.just_released(keys.fire);
+        let mut special_pressed = kb.just_pressed(keys.special);
+        let mut secondary = kb.pressed(keys.secondary);
+
+        if let Some(gamepad) = gamepads.iter().nth(id).copied() {
+            let button = |button_type| GamepadButton {
+                gamepad,
+                button_type,
+            };
+            let stick = axes
+                .get(GamepadAxis {
+                    gamepad,
+                    axis_type: GamepadAxisType::LeftStickX,
+                })
+                .unwrap_or(0.);
+            if stick.abs() > STICK_DEADZONE {
+                x += stick;
+            }
+            if buttons.pressed(button(GamepadButtonType::DPadLeft)) {
+                x -= 1.;
+            }
+            if buttons.pressed(button(GamepadButtonType::DPadRight)) {
+                x += 1.;
+            }
+            if buttons.just_pressed(button(GamepadButtonType::DPadLeft)) {
+                step = -1;
+            } else if buttons.just_pressed(button(GamepadButtonType::DPadRight)) {
+                step = 1;
+            }
+            fire |= buttons.pressed(button(GamepadButtonType::South));
+            fire_pressed |= buttons.just_pressed(button(GamepadButtonType::South));
+            fire_released |= buttons.just_released(button(GamepadButtonType::South));
+            special_pressed |= buttons.just_pressed(button(GamepadButtonType::East));
+            secondary |= buttons.pressed(button(GamepadButtonType::West));
+        }
+
+        *input = PlayerInput {
+            x: x.clamp(-1., 1.),
+            fire,
+            fire_pressed,
+            fire_released,
+            special_pressed,
+            secondary,
+            step,
+        };
+    }
+}
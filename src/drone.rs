@@ -0,0 +1,238 @@
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{Movable, Player, PlayerId, Velocity},
+    events::{PlayerDied, PowerUpCollected, ShotFired},
+    player::{player_fire_system, spawn_player_laser},
+    pool::EntityPool,
+    powerup::PowerUpKind,
+    settings::Settings,
+    AppState, GameClock, GameConfig, GameTextures, DRONE_FOLLOW, DRONE_MAX, DRONE_ORBIT_RADIUS,
+    DRONE_ORBIT_SPEED, DRONE_SIZE, DRONE_TRAIL_SPACING, TIME_STEP,
+};
+
+// -- option drones, collected through power ups. they shoot whenever their player does
+// and are all lost when it dies
+
+pub struct DronePlugin;
+
+impl Plugin for DronePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Playing).with_system(drone_reset_system))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(drone_trail_record_system)
+                    .with_system(drone_collect_system)
+                    .with_system(drone_steer_system.after(drone_trail_record_system))
+                    .with_system(drone_fire_system.after(player_fire_system))
+                    .with_system(drone_lost_system),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DroneMode {
+    Trail, // follow the path the player took
+    Orbit, // circle around the player
+}
+
+impl DroneMode {
+    pub const ALL: [DroneMode; 2] = [DroneMode::Trail, DroneMode::Orbit];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DroneMode::Trail => "TRAIL",
+            DroneMode::Orbit => "ORBIT",
+        }
+    }
+}
+
+// region: --- Components
+// ring buffer of the last player positions, only written while it moves
+// so the drones bunch up behind a ship standing still
+#[derive(Component)]
+pub struct PositionHistory {
+    positions: Vec<Vec2>,
+    head: usize, // newest entry
+}
+
+impl PositionHistory {
+    fn new(start: Vec2) -> Self {
+        Self {
+            positions: vec![start; DRONE_MAX * DRONE_TRAIL_SPACING + 1],
+            head: 0,
+        }
+    }
+
+    fn push(&mut self, position: Vec2) {
+        self.head = (self.head + 1) % self.positions.len();
+        self.positions[self.head] = position;
+    }
+
+    /// position `steps` moves ago, 0 is the newest
+    fn back(&self, steps: usize) -> Vec2 {
+        let len = self.positions.len();
+        self.positions[(self.head + len - steps % len) % len]
+    }
+}
+
+#[derive(Component)]
+pub struct Drone {
+    player: Entity,
+    slot: usize, // 0 is the closest
+}
+// endregion: --- Components
+
+fn drone_trail_record_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, Option<&mut PositionHistory>), With<Player>>,
+) {
+    for (entity, transform, history) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        match history {
+            Some(mut history) => {
+                if history.back(0) != position {
+                    history.push(position);
+                }
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(PositionHistory::new(position));
+            }
+        }
+    }
+}
+
+fn drone_collect_system(
+    mut commands: Commands,
+    mut collected_events: EventReader<PowerUpCollected>,
+    player_query: Query<&PlayerId, With<Player>>,
+    drone_query: Query<&Drone>,
+) {
+    // several pickups in one frame, the new drones are not in the query yet
+    let mut added: HashMap<Entity, usize> = HashMap::new();
+
+    for event in collected_events.iter() {
+        if event.kind != PowerUpKind::Option {
+            continue;
+        }
+        let player_id = match player_query.get(event.player) {
+            Ok(player_id) => *player_id,
+            Err(_) => continue,
+        };
+        let new = added.entry(event.player).or_default();
+        let slot = drone_query
+            .iter()
+            .filter(|drone| drone.player == event.player)
+            .count()
+            + *new;
+        if slot >= DRONE_MAX {
+            continue;
+        }
+        *new += 1;
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(1., 0.85, 0.3),
+                    custom_size: Some(Vec2::splat(DRONE_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(event.position.truncate().extend(1.5)),
+                ..Default::default()
+            })
+            .insert(Drone {
+                player: event.player,
+                slot,
+            })
+            .insert(player_id)
+            .insert(Movable)
+            .insert(Velocity { x: 0., y: 0. });
+    }
+}
+
+// velocity that takes the drone part of the way to its spot, movable_system does the rest
+fn drone_steer_system(
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
+    player_query: Query<(&Transform, &PositionHistory), With<Player>>,
+    mut drone_query: Query<(&Drone, &Transform, &mut Velocity), Without<Player>>,
+) {
+    let mut counts: HashMap<Entity, usize> = HashMap::new();
+    for (drone, _, _) in drone_query.iter() {
+        *counts.entry(drone.player).or_default() += 1;
+    }
+
+    let now = clock.elapsed() as f32;
+    for (drone, transform, mut velocity) in drone_query.iter_mut() {
+        let (player_tf, history) = match player_query.get(drone.player) {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+        let target = match settings.drone_mode {
+            DroneMode::Trail => history.back((drone.slot + 1) * DRONE_TRAIL_SPACING),
+            DroneMode::Orbit => {
+                let count = counts.get(&drone.player).copied().unwrap_or(1);
+                let angle = now * DRONE_ORBIT_SPEED + drone.slot as f32 * TAU / count as f32;
+                player_tf.translation.truncate() + Vec2::from_angle(angle) * DRONE_ORBIT_RADIUS
+            }
+        };
+        let step = (target - transform.translation.truncate()) * DRONE_FOLLOW
+            / (TIME_STEP * config.base_speed);
+        velocity.x = step.x;
+        velocity.y = step.y;
+    }
+}
+
+fn drone_fire_system(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    game_textures: Res<GameTextures>,
+    mut pool: ResMut<EntityPool>,
+    mut shot_fired_events: EventReader<ShotFired>,
+    drone_query: Query<(&Drone, &PlayerId, &Transform)>,
+) {
+    for shot in shot_fired_events.iter().filter(|shot| shot.from_player) {
+        for (_, player_id, transform) in drone_query
+            .iter()
+            .filter(|(drone, _, _)| drone.player == shot.by)
+        {
+            let translation = transform.translation + Vec3::new(0., DRONE_SIZE / 2., 0.5);
+            spawn_player_laser(
+                &mut commands,
+                &mut pool,
+                &config,
+                &game_textures,
+                shot.by,
+                *player_id,
+                translation,
+                0.,
+            );
+        }
+    }
+}
+
+fn drone_lost_system(
+    mut commands: Commands,
+    mut died_events: EventReader<PlayerDied>,
+    drone_query: Query<(Entity, &Drone)>,
+) {
+    for event in died_events.iter() {
+        for (entity, drone) in drone_query.iter() {
+            if drone.player == event.player {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn drone_reset_system(mut commands: Commands, query: Query<Entity, With<Drone>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
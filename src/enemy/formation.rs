@@ -1,6 +1,6 @@
-use crate::{WinSize, BASE_SPEED, FORMATION_MEMBERS_MAX};
+use crate::{GameConfig, WinSize, ENEMY_FLEE_AFTER, FORMATIONS_PER_WAVE};
 use bevy::{ecs::system::Resource, prelude::Component};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 // --Component - Enemy Formation (per enemy)
 #[derive(Clone, Component)]
@@ -10,6 +10,10 @@ pub struct Formation {
     pub pivot: (f32, f32),
     pub speed: f32,
     pub angle: f32, // change per tick
+    pub flee_after: f32,
+    pub spawned_at: f32, // set per member on spawn
+    pub group: u32,      // unique per formation
+    pub wave: u32,
 }
 
 // -Ressource - Formation Maker
@@ -18,14 +22,25 @@ pub struct Formation {
 pub struct FormationMaker {
     current_template: Option<Formation>,
     current_members: u32,
+    groups_made: u32,
 }
 
 // Formation factory
 impl FormationMaker {
-    pub fn make(&mut self, win_size: &WinSize) -> Formation {
+    /// wave currently spawning
+    pub fn wave(&self) -> u32 {
+        self.current_template.as_ref().map_or(0, |tmpl| tmpl.wave)
+    }
+
+    pub fn make(
+        &mut self,
+        win_size: &WinSize,
+        config: &GameConfig,
+        rng: &mut impl Rng,
+    ) -> Formation {
         match (
             &self.current_template,
-            self.current_members >= FORMATION_MEMBERS_MAX,
+            self.current_members >= config.formation_members_max,
         ) {
             // if has current template and still within max members
             (Some(tmpl), false) => {
@@ -34,8 +49,6 @@ impl FormationMaker {
             }
             // if first formation or previous formation is full (need to create a new one)
             (None, _) | (_, true) => {
-                let mut rng = thread_rng();
-
                 // compute the start x/y
                 let w_span = win_size.w / 2. + 100.;
                 let h_span = win_size.h / 2. + 100.;
@@ -55,7 +68,10 @@ impl FormationMaker {
                 let angle = (y - pivot.1).atan2(x - pivot.0);
 
                 // speed (fixed for now)
-                let speed = BASE_SPEED;
+                let speed = config.base_speed;
+
+                // how long the formation stays before fleeing
+                let flee_after = rng.gen_range(ENEMY_FLEE_AFTER.0..ENEMY_FLEE_AFTER.1);
 
                 // create the formation
                 let formation = Formation {
@@ -64,7 +80,12 @@ impl FormationMaker {
                     pivot,
                     speed,
                     angle,
+                    flee_after,
+                    spawned_at: 0.,
+                    group: self.groups_made,
+                    wave: self.groups_made / FORMATIONS_PER_WAVE,
                 };
+                self.groups_made += 1;
 
                 // store as template
                 self.current_template = Some(formation.clone());
@@ -1,16 +1,30 @@
 use std::f32::consts::PI;
 
 use crate::{
-    components::{Enemy, FromEnemy, Laser, Movable, SpriteSize, Velocity},
-    EnemyCount, GameTextures, WinSize, BASE_SPEED, ENEMY_LASER_SIZE, ENEMY_MAX, ENEMY_SIZE,
-    SPRITE_SCALE, TIME_STEP,
+    components::{
+        Collider, DespawnPolicy, Enemy, EnemyArchetype, FromEnemy, Health, Laser, Movable, Owner,
+        SpriteSize, Swept, Velocity,
+    },
+    events::ShotFired,
+    playing_criteria,
+    pool::{EntityPool, PoolKind},
+    settings::Settings,
+    AppState, EnemyCount, GameClock, GameConfig, GameRng, GameTextures, Wave, WinSize,
+    ENEMY_DESPAWN_MARGIN, ENEMY_HITBOX, ENEMY_LASER_HITBOX, PROJECTILE_DESPAWN_MARGIN,
+    SWEPT_SPEED_MIN, TIME_STEP,
 };
 use bevy::{ecs::schedule::ShouldRun, prelude::*, time::FixedTimestep, transform};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
-use self::formation::{Formation, FormationMaker};
+use self::{
+    formation::FormationMaker,
+    wave::{wave_tracker_system, WaveTracker},
+};
+
+pub use self::formation::Formation;
 
 mod formation;
+mod wave;
 
 pub struct EnemyPlugin;
 
@@ -18,17 +32,23 @@ impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         //add enemy a little bit delay to stage
         app.insert_resource(FormationMaker::default())
+            .insert_resource(WaveTracker::default())
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(enemy_reset_system))
             .add_system_set(
                 SystemSet::new()
-                    .with_run_criteria(FixedTimestep::step(0.2))
+                    .with_run_criteria(FixedTimestep::step(0.2).chain(playing_criteria))
                     .with_system(enemy_spawn_system),
             )
             .add_system_set(
                 SystemSet::new()
-                    .with_run_criteria(enemy_fire_criteria)
+                    .with_run_criteria(enemy_fire_criteria.chain(playing_criteria))
                     .with_system(enemy_fire_system),
             )
-            .add_system(enemy_move_system);
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(enemy_move_system)
+                    .with_system(wave_tracker_system),
+            );
 
         //app.add_startup_system_to_stage(StartupStage::PostStartup, enemy_spawn_system);
         //app.add_system(enemy_spawn_system);
@@ -40,45 +60,66 @@ fn enemy_spawn_system(
     game_textures: Res<GameTextures>,
     mut enemy_count: ResMut<EnemyCount>,
     mut formation_maker: ResMut<FormationMaker>,
+    mut wave_tracker: ResMut<WaveTracker>,
+    mut wave: ResMut<Wave>,
+    mut game_rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
     win_size: Res<WinSize>,
+    clock: Res<GameClock>,
 ) {
-    if enemy_count.0 < ENEMY_MAX {
+    if enemy_count.0 < config.enemy_max {
         /// get formation and start x/y
-        let formation = formation_maker.make(&win_size);
+        let mut formation = formation_maker.make(&win_size, &config, &mut game_rng.rng);
+        formation.spawned_at = clock.elapsed() as f32;
         let (x, y) = formation.start;
+        wave_tracker.spawned(formation.wave);
+        wave.0 = formation.wave;
 
         commands
             .spawn_bundle(SpriteBundle {
                 texture: game_textures.enemy.clone(),
                 transform: Transform {
                     translation: Vec3::new(x, y, 10.),
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+                    scale: Vec3::new(config.sprite_scale, config.sprite_scale, 1.),
                     ..Default::default()
                 },
                 ..Default::default()
             })
             .insert(Enemy)
+            .insert(EnemyArchetype::Grunt)
+            .insert(Health(EnemyArchetype::Grunt.health()))
             .insert(formation)
-            .insert(SpriteSize::from(ENEMY_SIZE));
+            .insert(Collider::enemy())
+            .insert(config.hitbox(EnemyArchetype::Grunt.key(), ENEMY_HITBOX))
+            .insert(DespawnPolicy::AfterEntering {
+                margin: ENEMY_DESPAWN_MARGIN,
+                entered: false,
+            });
 
         enemy_count.0 += 1;
     }
 }
 
-//TODO: enemy out of sight is not despawning
-// all enemies share the same movement pattern
+// all enemies share the same movement pattern, until their formation flees
 
 fn enemy_move_system(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     mut query: Query<(&mut Transform, &mut Formation), With<Enemy>>,
 ) {
-    let now = time.seconds_since_startup() as f32; // casting;
+    let now = clock.elapsed() as f32; // casting;
     for (mut transform, mut formation) in query.iter_mut() {
         //current position
         let (x_org, y_org) = (transform.translation.x, transform.translation.y);
 
-        //max distance
-        let max_distance = TIME_STEP * formation.speed;
+        //max distance, none while the game clock stands still
+        let max_distance = TIME_STEP * formation.speed * clock.scale();
+
+        // time is up, leave the stage upwards on the nearest side
+        if now > formation.spawned_at + formation.flee_after {
+            let flee_dir = Vec2::new(if x_org < 0. { -1. } else { 1. }, 1.).normalize();
+            transform.translation += (flee_dir * max_distance).extend(0.);
+            continue;
+        }
 
         //fixtures, hardcoded for now
 
@@ -122,9 +163,12 @@ fn enemy_move_system(
     }
 }
 
-fn enemy_fire_criteria() -> ShouldRun {
+fn enemy_fire_criteria(settings: Res<Settings>, mut game_rng: ResMut<GameRng>) -> ShouldRun {
     // 60. is kinda magic number for framerate equivalent
-    if thread_rng().gen_bool(1. / 60.) {
+    if game_rng
+        .rng
+        .gen_bool(settings.difficulty.enemy_fire_scale() / 60.)
+    {
         ShouldRun::Yes
     } else {
         ShouldRun::No
@@ -133,28 +177,58 @@ fn enemy_fire_criteria() -> ShouldRun {
 
 fn enemy_fire_system(
     mut commands: Commands,
+    config: Res<GameConfig>,
     game_textures: Res<GameTextures>,
-    enemy_query: Query<&Transform, With<Enemy>>,
+    mut pool: ResMut<EntityPool>,
+    mut shot_fired_events: EventWriter<ShotFired>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
 ) {
-    for &tf in enemy_query.iter() {
+    for (enemy_entity, tf) in enemy_query.iter() {
         //spawn enemy laser
         let (x, y) = (tf.translation.x, tf.translation.y);
-        commands
-            .spawn_bundle(SpriteBundle {
-                texture: game_textures.enemy_laser.clone(),
-                transform: Transform {
-                    translation: Vec3::new(x, y - 15., 0.),
-                    rotation: Quat::from_rotation_x(PI),
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                    ..Default::default()
-                },
+        let translation = Vec3::new(x, y - 15., 0.);
+        //make laser fall down
+        let velocity = Velocity { x: 0., y: -1. };
+        let swept = velocity.length() * config.base_speed > SWEPT_SPEED_MIN;
+
+        let bundle = SpriteBundle {
+            texture: game_textures.enemy_laser.clone(),
+            transform: Transform {
+                translation,
+                rotation: Quat::from_rotation_x(PI),
+                scale: Vec3::new(config.sprite_scale, config.sprite_scale, 1.),
                 ..Default::default()
-            })
+            },
+            ..Default::default()
+        };
+        let mut laser = pool.acquire(&mut commands, PoolKind::EnemyLaser, bundle);
+        laser
             .insert(Laser)
-            .insert(SpriteSize::from(ENEMY_LASER_SIZE))
             .insert(FromEnemy)
-            .insert(Movable { auto_despawn: true })
-            //make laser fall down
-            .insert(Velocity { x: 0., y: -1. });
+            .insert(Owner(enemy_entity))
+            .insert(Collider::enemy_laser())
+            .insert(config.hitbox("enemy_laser", ENEMY_LASER_HITBOX))
+            .insert(Movable)
+            .insert(DespawnPolicy::OffScreen {
+                margin: PROJECTILE_DESPAWN_MARGIN,
+            })
+            .insert(velocity);
+        if swept {
+            laser.insert(Swept::from(translation));
+        }
+
+        shot_fired_events.send(ShotFired {
+            by: enemy_entity,
+            position: translation,
+            from_player: false,
+        });
+    }
+}
+
+fn enemy_reset_system(mut commands: Commands, query: Query<Entity, With<Enemy>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
     }
+    commands.insert_resource(FormationMaker::default());
+    commands.insert_resource(WaveTracker::default());
 }
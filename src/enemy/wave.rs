@@ -0,0 +1,51 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use super::formation::FormationMaker;
+use crate::events::{EnemyEscaped, EnemyKilled, WaveCleared};
+
+// -Ressource - enemies still alive per wave
+#[derive(Default)]
+pub struct WaveTracker {
+    alive: HashMap<u32, u32>,
+}
+
+impl WaveTracker {
+    pub fn spawned(&mut self, wave: u32) {
+        *self.alive.entry(wave).or_default() += 1;
+    }
+
+    fn removed(&mut self, wave: u32) {
+        if let Some(alive) = self.alive.get_mut(&wave) {
+            *alive = alive.saturating_sub(1);
+        }
+    }
+}
+
+pub fn wave_tracker_system(
+    mut killed_events: EventReader<EnemyKilled>,
+    mut escaped_events: EventReader<EnemyEscaped>,
+    mut wave_cleared_events: EventWriter<WaveCleared>,
+    mut tracker: ResMut<WaveTracker>,
+    formation_maker: Res<FormationMaker>,
+) {
+    for event in killed_events.iter() {
+        tracker.removed(event.wave);
+    }
+    for event in escaped_events.iter() {
+        tracker.removed(event.wave);
+    }
+
+    // a wave is over once the maker moved past it and nobody is left
+    let current_wave = formation_maker.wave();
+    let cleared: Vec<u32> = tracker
+        .alive
+        .iter()
+        .filter(|(wave, alive)| **wave < current_wave && **alive == 0)
+        .map(|(wave, _)| *wave)
+        .collect();
+
+    for wave in cleared {
+        tracker.alive.remove(&wave);
+        wave_cleared_events.send(WaveCleared { wave });
+    }
+}
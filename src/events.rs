@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{EnemyArchetype, PlayerId},
+    powerup::PowerUpKind,
+};
+
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EnemyKilled>()
+            .add_event::<EnemyEscaped>()
+            .add_event::<PlayerHit>()
+            .add_event::<PlayerDied>()
+            .add_event::<PowerUpCollected>()
+            .add_event::<ShotFired>()
+            .add_event::<LaserImpact>()
+            .add_event::<WaveCleared>()
+            .add_event::<GroupCleared>()
+            .add_event::<ExplosionToSpawn>();
+    }
+}
+
+// region --- Enemy Events
+pub struct EnemyKilled {
+    pub archetype: EnemyArchetype,
+    pub position: Vec3,
+    pub by: Entity, // owner of the projectile
+    pub player: PlayerId,
+    pub group: u32,
+    pub wave: u32,
+}
+
+// enemy left the arena alive
+pub struct EnemyEscaped {
+    pub archetype: EnemyArchetype,
+    pub position: Vec3,
+    pub group: u32,
+    pub wave: u32,
+}
+
+// whole formation group shot down before any member escaped
+pub struct GroupCleared {
+    pub group: u32,
+    pub position: Vec3, // last kill
+    pub bonus: u64,
+}
+
+// every enemy of the wave was killed or escaped
+pub struct WaveCleared {
+    pub wave: u32,
+}
+// endregion --- Enemy Events
+
+// region --- Player Events
+pub struct PlayerHit {
+    pub player: Entity,
+    pub position: Vec3,
+    pub by: Entity, // owner of the projectile
+}
+
+pub struct PlayerDied {
+    pub player: Entity,
+    pub position: Vec3,
+}
+
+pub struct PowerUpCollected {
+    pub player: Entity,
+    pub kind: PowerUpKind,
+    pub position: Vec3,
+}
+// endregion --- Player Events
+
+// region --- Common Events
+pub struct ShotFired {
+    pub by: Entity,
+    pub position: Vec3,
+    pub from_player: bool,
+}
+
+// laser stopped by whatever it hit
+pub struct LaserImpact {
+    pub position: Vec3,
+}
+
+pub struct ExplosionToSpawn(pub Vec3);
+// endregion --- Common Events
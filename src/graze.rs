@@ -0,0 +1,221 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    collision::{collision_detection_system, CollisionEvent},
+    components::{FromEnemy, Laser, Player, PlayerId, SpriteSize},
+    pool::Pooled,
+    score::Scores,
+    AppState, GameClock, GameConfig, PlayerStates, GRAZE_MARGIN, GRAZE_METER_GAIN, GRAZE_POINTS,
+    GRAZE_SPARK_TIME,
+};
+
+pub struct GrazePlugin;
+
+impl Plugin for GrazePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GrazeMeter::default())
+            .add_startup_system(graze_hud_setup_system)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(graze_reset_system))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(graze_system.after(collision_detection_system))
+                    .with_system(graze_spark_system),
+            )
+            .add_system(graze_hud_system);
+    }
+}
+
+// -Ressource - graze count and meter (0..1)
+#[derive(Default)]
+pub struct GrazeMeter {
+    pub count: u32,
+    pub value: f32,
+}
+
+// region: --- Components
+// laser already paid out its graze or hit (removed again when the pool reuses it)
+#[derive(Component)]
+pub struct Grazed;
+
+// laser is inside the graze zone of this ship, pays out once it leaves without a hit
+#[derive(Component)]
+pub struct Grazing(Entity);
+
+#[derive(Component)]
+struct GrazeSpark(Timer);
+
+#[derive(Component)]
+struct GrazeMeterFrame;
+
+#[derive(Component)]
+struct GrazeMeterFill;
+// endregion: --- Components
+
+fn graze_system(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut scores: ResMut<Scores>,
+    mut meter: ResMut<GrazeMeter>,
+    mut collision_events: EventReader<CollisionEvent>,
+    player_query: Query<(Entity, &PlayerId, &Transform, Option<&SpriteSize>), With<Player>>,
+    laser_query: Query<
+        (Entity, &Transform, Option<&Grazing>),
+        (
+            With<Laser>,
+            With<FromEnemy>,
+            Without<Grazed>,
+            Without<Pooled>,
+        ),
+    >,
+) {
+    // lasers touching a player this frame are hits, not grazes
+    let hitting: HashSet<Entity> = collision_events
+        .iter()
+        .filter_map(|event| event.ordered(|entity| !player_query.contains(entity)))
+        .filter(|(_, other)| player_query.contains(*other))
+        .map(|(laser, _)| laser)
+        .collect();
+
+    // the whole ship plus a margin, so the zone reaches past the hitbox
+    let radius = |size: Option<&SpriteSize>| {
+        size.map_or(0., |size| size.0.length() / 2. * config.sprite_scale) + GRAZE_MARGIN
+    };
+
+    for (laser_entity, laser_tf, grazing) in laser_query.iter() {
+        let laser_pos = laser_tf.translation.truncate();
+        if hitting.contains(&laser_entity) {
+            commands
+                .entity(laser_entity)
+                .insert(Grazed)
+                .remove::<Grazing>();
+            continue;
+        }
+
+        let grazing = match grazing {
+            Some(grazing) => grazing.0,
+            None => {
+                // first ship it comes close to
+                let near = player_query.iter().find(|(_, _, player_tf, size)| {
+                    laser_pos.distance(player_tf.translation.truncate()) <= radius(*size)
+                });
+                if let Some((player_entity, _, _, _)) = near {
+                    commands.entity(laser_entity).insert(Grazing(player_entity));
+                }
+                continue;
+            }
+        };
+
+        let (player_id, player_pos) = match player_query.get(grazing) {
+            Ok((_, player_id, player_tf, size)) => {
+                let player_pos = player_tf.translation.truncate();
+                if laser_pos.distance(player_pos) <= radius(size) {
+                    continue;
+                }
+                (player_id, player_pos)
+            }
+            // the ship is gone, nothing to pay out
+            Err(_) => {
+                commands.entity(laser_entity).remove::<Grazing>();
+                continue;
+            }
+        };
+
+        // left the zone without a hit
+        commands
+            .entity(laser_entity)
+            .insert(Grazed)
+            .remove::<Grazing>();
+        scores.0[player_id.0].points += GRAZE_POINTS;
+        meter.count += 1;
+        meter.value = (meter.value + GRAZE_METER_GAIN).min(1.);
+
+        // spark halfway between the two
+        let spark_pos = ((laser_pos + player_pos) / 2.).extend(40.);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(0.6, 0.9, 1.),
+                    custom_size: Some(Vec2::splat(6.)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(spark_pos),
+                ..Default::default()
+            })
+            .insert(GrazeSpark(Timer::from_seconds(GRAZE_SPARK_TIME, false)));
+    }
+}
+
+// grow and fade out
+fn graze_spark_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut query: Query<(Entity, &mut GrazeSpark, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut spark, mut transform, mut sprite) in query.iter_mut() {
+        spark.0.tick(clock.delta());
+        transform.scale = Vec3::splat(1. + 2. * spark.0.percent());
+        sprite.color.set_a(spark.0.percent_left());
+        if spark.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn graze_hud_setup_system(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(12.),
+                    top: Val::Px(42.),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Px(120.), Val::Px(6.)),
+                ..Default::default()
+            },
+            color: Color::rgba(1., 1., 1., 0.15).into(),
+            ..Default::default()
+        })
+        .insert(GrazeMeterFrame)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                        ..Default::default()
+                    },
+                    color: Color::rgb(0.6, 0.9, 1.).into(),
+                    ..Default::default()
+                })
+                .insert(GrazeMeterFill);
+        });
+}
+
+fn graze_hud_system(
+    meter: Res<GrazeMeter>,
+    player_states: Res<PlayerStates>,
+    mut frame_query: Query<&mut Style, (With<GrazeMeterFrame>, Without<GrazeMeterFill>)>,
+    mut query: Query<&mut Style, With<GrazeMeterFill>>,
+) {
+    // below the score lines, one per player
+    if player_states.is_changed() {
+        let lines = player_states.joined().count().max(1) as f32;
+        for mut style in frame_query.iter_mut() {
+            style.position.top = Val::Px(8. + 34. * lines);
+        }
+    }
+    if !meter.is_changed() {
+        return;
+    }
+    for mut style in query.iter_mut() {
+        style.size.width = Val::Percent(meter.value * 100.);
+    }
+}
+
+fn graze_reset_system(mut commands: Commands, query: Query<Entity, With<GrazeSpark>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.insert_resource(GrazeMeter::default());
+}
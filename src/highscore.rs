@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    platform,
+    score::Scores,
+    settings::Settings,
+    ui::{despawn_screen, label, screen_root},
+    AppState, GameFonts, GameMode, GameRng, KillCount, Wave, HIGH_SCORE_ENTRIES,
+};
+
+const HIGH_SCORES_KEY: &str = "highscores";
+const NAME_LEN: usize = 3;
+const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .insert_resource(NameEntry::default())
+            .add_system_set(
+                SystemSet::on_enter(AppState::GameOver).with_system(game_over_setup_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver)
+                    .with_system(name_entry_system)
+                    .with_system(name_entry_text_system.after(name_entry_system)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver).with_system(despawn_screen::<GameOverUi>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Leaderboard).with_system(leaderboard_setup_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Leaderboard).with_system(leaderboard_input_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Leaderboard)
+                    .with_system(despawn_screen::<LeaderboardUi>),
+            );
+    }
+}
+
+// region: --- Resources
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u64,
+    pub wave: u32,
+    pub kills: u32,
+    pub date: u64, // unix seconds
+    pub seed: u64,
+}
+
+// top entries per game mode, best first
+#[derive(Serialize, Deserialize, Default)]
+pub struct HighScores {
+    tables: BTreeMap<String, Vec<HighScoreEntry>>,
+}
+
+impl HighScores {
+    fn load() -> Self {
+        platform::load(HIGH_SCORES_KEY)
+            .and_then(|saved| match ron::from_str(&saved) {
+                Ok(high_scores) => Some(high_scores),
+                Err(err) => {
+                    warn!("ignoring broken high score table: {}", err);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(saved) => platform::save(HIGH_SCORES_KEY, &saved),
+            Err(err) => warn!("could not serialize high scores: {}", err),
+        }
+    }
+
+    pub fn table(&self, mode: GameMode) -> &[HighScoreEntry] {
+        self.tables.get(mode.key()).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn qualifies(&self, mode: GameMode, score: u64) -> bool {
+        let table = self.table(mode);
+        score > 0
+            && (table.len() < HIGH_SCORE_ENTRIES || table.iter().any(|entry| score > entry.score))
+    }
+
+    /// inserts and trims the table, returns the rank (0 based)
+    fn insert(&mut self, mode: GameMode, entry: HighScoreEntry) -> usize {
+        let table = self.tables.entry(mode.key().to_string()).or_default();
+        let rank = table
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(table.len());
+        table.insert(rank, entry);
+        table.truncate(HIGH_SCORE_ENTRIES);
+        rank
+    }
+}
+
+// result of the finished game and the name typed so far
+#[derive(Default)]
+struct NameEntry {
+    result: Option<HighScoreEntry>,
+    letters: [usize; NAME_LEN], // index into LETTERS
+    cursor: usize,
+    last_rank: Option<(GameMode, usize)>, // highlighted on the leaderboard
+}
+
+impl NameEntry {
+    fn name(&self) -> String {
+        self.letters
+            .iter()
+            .map(|&i| LETTERS[i] as char)
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+}
+
+// mode shown on the leaderboard
+struct LeaderboardMode(GameMode);
+// endregion: --- Resources
+
+// region: --- Components
+#[derive(Component)]
+struct GameOverUi;
+
+#[derive(Component)]
+struct NameEntryText;
+
+#[derive(Component)]
+struct LeaderboardUi;
+// endregion: --- Components
+
+fn game_over_setup_system(
+    mut commands: Commands,
+    fonts: Res<GameFonts>,
+    settings: Res<Settings>,
+    high_scores: Res<HighScores>,
+    mode: Res<GameMode>,
+    scores: Res<Scores>,
+    kill_count: Res<KillCount>,
+    wave: Res<Wave>,
+    game_rng: Res<GameRng>,
+    mut name_entry: ResMut<NameEntry>,
+) {
+    let lang = settings.language;
+    // co-op goes on the board as a team
+    let points = scores.total();
+    let qualifies = high_scores.qualifies(*mode, points);
+    *name_entry = NameEntry {
+        result: qualifies.then(|| HighScoreEntry {
+            name: String::new(),
+            score: points,
+            wave: wave.0 + 1,
+            kills: kill_count.0,
+            date: platform::unix_millis() / 1000,
+            seed: game_rng.seed,
+        }),
+        ..Default::default()
+    };
+
+    commands
+        .spawn_bundle(screen_root())
+        .insert(GameOverUi)
+        .with_children(|parent| {
+            parent.spawn_bundle(label(&fonts, lang.tr("GAME OVER"), 64., Color::WHITE));
+            parent.spawn_bundle(label(
+                &fonts,
+                format!("{:08}", points),
+                36.,
+                Color::rgb(1., 0.85, 0.3),
+            ));
+
+            if qualifies {
+                parent.spawn_bundle(label(
+                    &fonts,
+                    lang.tr("NEW HIGH SCORE - ENTER YOUR NAME"),
+                    24.,
+                    Color::WHITE,
+                ));
+                parent
+                    .spawn_bundle(label(&fonts, "", 56., Color::WHITE))
+                    .insert(NameEntryText);
+                parent.spawn_bundle(label(
+                    &fonts,
+                    lang.tr("UP/DOWN letter   LEFT/RIGHT move   ENTER confirm"),
+                    18.,
+                    Color::GRAY,
+                ));
+            } else {
+                parent.spawn_bundle(label(&fonts, lang.tr("press ENTER"), 18., Color::GRAY));
+            }
+        });
+}
+
+fn name_entry_system(
+    mut kb: ResMut<Input<KeyCode>>,
+    mut name_entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mode: Res<GameMode>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let cursor = name_entry.cursor;
+    let letter = name_entry.letters[cursor];
+    if kb.just_pressed(KeyCode::Up) {
+        name_entry.letters[cursor] = (letter + 1) % LETTERS.len();
+    }
+    if kb.just_pressed(KeyCode::Down) {
+        name_entry.letters[cursor] = (letter + LETTERS.len() - 1) % LETTERS.len();
+    }
+    if kb.just_pressed(KeyCode::Left) {
+        name_entry.cursor = cursor.saturating_sub(1);
+    }
+    if kb.just_pressed(KeyCode::Right) {
+        name_entry.cursor = (cursor + 1).min(NAME_LEN - 1);
+    }
+
+    if kb.clear_just_pressed(KeyCode::Return) {
+        let name = name_entry.name();
+        if let Some(mut entry) = name_entry.result.take() {
+            entry.name = if name.is_empty() {
+                "???".to_string()
+            } else {
+                name
+            };
+            let rank = high_scores.insert(*mode, entry);
+            high_scores.save();
+            name_entry.last_rank = Some((*mode, rank));
+        }
+        let _ = state.set(AppState::Leaderboard);
+    }
+}
+
+fn name_entry_text_system(
+    name_entry: Res<NameEntry>,
+    mut query: Query<&mut Text, With<NameEntryText>>,
+) {
+    if !name_entry.is_changed() {
+        return;
+    }
+    for mut text in query.iter_mut() {
+        let style = text.sections[0].style.clone();
+        text.sections = name_entry
+            .letters
+            .iter()
+            .enumerate()
+            .map(|(i, &letter)| {
+                let color = if i == name_entry.cursor {
+                    Color::rgb(1., 0.85, 0.3)
+                } else {
+                    Color::WHITE
+                };
+                // spaces would collapse, show them as underscores
+                let value = match LETTERS[letter] {
+                    b' ' => "_ ".to_string(),
+                    c => format!("{} ", c as char),
+                };
+                TextSection::new(
+                    value,
+                    TextStyle {
+                        color,
+                        ..style.clone()
+                    },
+                )
+            })
+            .collect();
+    }
+}
+
+fn leaderboard_setup_system(
+    mut commands: Commands,
+    fonts: Res<GameFonts>,
+    settings: Res<Settings>,
+    high_scores: Res<HighScores>,
+    mode: Res<GameMode>,
+    name_entry: Res<NameEntry>,
+    shown: Option<Res<LeaderboardMode>>,
+) {
+    let lang = settings.language;
+    let shown = shown.map_or(*mode, |shown| shown.0);
+    commands.insert_resource(LeaderboardMode(shown));
+
+    commands
+        .spawn_bundle(screen_root())
+        .insert(LeaderboardUi)
+        .with_children(|parent| {
+            parent.spawn_bundle(label(&fonts, lang.tr("HIGH SCORES"), 48., Color::WHITE));
+            parent.spawn_bundle(label(
+                &fonts,
+                format!("< {} >", shown.name()),
+                24.,
+                Color::rgb(0.6, 0.9, 1.),
+            ));
+
+            let table = high_scores.table(shown);
+            if table.is_empty() {
+                parent.spawn_bundle(label(&fonts, lang.tr("no entries yet"), 22., Color::GRAY));
+            }
+            for (rank, entry) in table.iter().enumerate() {
+                let color = if name_entry.last_rank == Some((shown, rank)) {
+                    Color::rgb(1., 0.85, 0.3)
+                } else {
+                    Color::WHITE
+                };
+                let row = format!(
+                    "{:>2}. {:<3}  {:>8}  W{:<3} K{:<5} {}  #{:016x}",
+                    rank + 1,
+                    entry.name,
+                    entry.score,
+                    entry.wave,
+                    entry.kills,
+                    platform::format_date(entry.date),
+                    entry.seed
+                );
+                parent.spawn_bundle(label(&fonts, row, 20., color));
+            }
+
+            parent.spawn_bundle(label(
+                &fonts,
+                lang.tr("LEFT/RIGHT mode   ENTER play again"),
+                18.,
+                Color::GRAY,
+            ));
+        });
+}
+
+fn leaderboard_input_system(
+    mut commands: Commands,
+    mut kb: ResMut<Input<KeyCode>>,
+    mut shown: ResMut<LeaderboardMode>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let modes = GameMode::ALL;
+    let index = modes.iter().position(|mode| *mode == shown.0).unwrap_or(0);
+    let next = if kb.just_pressed(KeyCode::Right) {
+        Some((index + 1) % modes.len())
+    } else if kb.just_pressed(KeyCode::Left) {
+        Some((index + modes.len() - 1) % modes.len())
+    } else {
+        None
+    };
+
+    if let Some(next) = next {
+        if next != index {
+            // rebuild the screen for the other mode
+            shown.0 = modes[next];
+            let _ = state.restart();
+        }
+    }
+
+    if kb.clear_just_pressed(KeyCode::Return) {
+        commands.remove_resource::<LeaderboardMode>();
+        let _ = state.set(AppState::CharacterSelect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u64) -> HighScoreEntry {
+        HighScoreEntry {
+            name: "AAA".to_string(),
+            score,
+            wave: 1,
+            kills: 0,
+            date: 0,
+            seed: 0,
+        }
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<u64> {
+        high_scores
+            .table(GameMode::Arcade)
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn insert_keeps_best_first() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(GameMode::Arcade, entry(200)), 0);
+        assert_eq!(high_scores.insert(GameMode::Arcade, entry(500)), 0);
+        assert_eq!(high_scores.insert(GameMode::Arcade, entry(300)), 1);
+        // a tie goes below the older entry
+        assert_eq!(high_scores.insert(GameMode::Arcade, entry(300)), 2);
+        assert_eq!(scores(&high_scores), vec![500, 300, 300, 200]);
+        assert!(high_scores.table(GameMode::CoOp).is_empty());
+    }
+
+    #[test]
+    fn insert_truncates_to_max_entries() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=HIGH_SCORE_ENTRIES as u64 + 3 {
+            high_scores.insert(GameMode::Arcade, entry(score * 10));
+        }
+        let table = scores(&high_scores);
+        assert_eq!(table.len(), HIGH_SCORE_ENTRIES);
+        assert_eq!(table[0], (HIGH_SCORE_ENTRIES as u64 + 3) * 10);
+        assert_eq!(*table.last().unwrap(), 40);
+
+        // falls off the end right away
+        let rank = high_scores.insert(GameMode::Arcade, entry(5));
+        assert_eq!(rank, HIGH_SCORE_ENTRIES);
+        assert_eq!(scores(&high_scores), table);
+    }
+
+    #[test]
+    fn qualifies_until_table_is_full() {
+        let mut high_scores = HighScores::default();
+        assert!(!high_scores.qualifies(GameMode::Arcade, 0));
+        assert!(high_scores.qualifies(GameMode::Arcade, 1));
+        for _ in 0..HIGH_SCORE_ENTRIES {
+            high_scores.insert(GameMode::Arcade, entry(100));
+        }
+        assert!(!high_scores.qualifies(GameMode::Arcade, 100));
+        assert!(high_scores.qualifies(GameMode::Arcade, 101));
+        assert!(high_scores.qualifies(GameMode::CoOp, 1));
+    }
+}
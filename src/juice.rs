@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::{
+    events::{EnemyKilled, GroupCleared, PlayerDied, PlayerHit},
+    AppState, GameClock, HIT_STOP_TIME, SHAKE_MAX_ANGLE, SHAKE_MAX_OFFSET, TRAUMA_DEATH,
+    TRAUMA_DECAY, TRAUMA_GROUP_CLEAR, TRAUMA_HIT, TRAUMA_KILL,
+};
+
+pub struct JuicePlugin;
+
+impl Plugin for JuicePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ReducedMotion::default())
+            .insert_resource(ShakeIntensity(1.))
+            .add_system(camera_trauma_system)
+            .add_system(camera_shake_system.after(camera_trauma_system))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(hit_stop_system));
+    }
+}
+
+// -Ressource - accessibility, no shake and no hit-stop when on
+#[derive(Default)]
+pub struct ReducedMotion(pub bool);
+
+// -Ressource - 0..1 of the full camera shake
+pub struct ShakeIntensity(pub f32);
+
+// -- Component - camera shakes by trauma² (0..1), trauma wears off over time
+#[derive(Component, Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+impl CameraShake {
+    pub fn add(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+}
+
+fn camera_trauma_system(
+    mut query: Query<&mut CameraShake>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut hit_events: EventReader<PlayerHit>,
+    mut died_events: EventReader<PlayerDied>,
+    mut cleared_events: EventReader<GroupCleared>,
+) {
+    let trauma = killed_events.iter().count() as f32 * TRAUMA_KILL
+        + hit_events.iter().count() as f32 * TRAUMA_HIT
+        + died_events.iter().count() as f32 * TRAUMA_DEATH
+        + cleared_events.iter().count() as f32 * TRAUMA_GROUP_CLEAR;
+    if trauma <= 0. {
+        return;
+    }
+    for mut shake in query.iter_mut() {
+        shake.add(trauma);
+    }
+}
+
+fn camera_shake_system(
+    time: Res<Time>,
+    reduced_motion: Res<ReducedMotion>,
+    intensity: Res<ShakeIntensity>,
+    mut query: Query<(&mut CameraShake, &mut Transform)>,
+) {
+    // real time, keeps shaking through hit-stop
+    let t = time.seconds_since_startup() as f32;
+    for (mut shake, mut transform) in query.iter_mut() {
+        shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_seconds()).max(0.);
+        let amount = if reduced_motion.0 {
+            0.
+        } else {
+            shake.trauma * shake.trauma * intensity.0
+        };
+
+        // cheap smooth noise, a few detuned sines per axis
+        let noise = |seed: f32| ((t * 37. + seed).sin() + (t * 23.1 + seed * 2.).sin()) / 2.;
+        transform.translation.x = SHAKE_MAX_OFFSET * amount * noise(0.);
+        transform.translation.y = SHAKE_MAX_OFFSET * amount * noise(10.);
+        transform.rotation = Quat::from_rotation_z(SHAKE_MAX_ANGLE * amount * noise(20.));
+    }
+}
+
+// big moments freeze the game for a few frames, only the game clock stops
+// so events keep flowing
+fn hit_stop_system(
+    reduced_motion: Res<ReducedMotion>,
+    mut clock: ResMut<GameClock>,
+    mut died_events: EventReader<PlayerDied>,
+    mut cleared_events: EventReader<GroupCleared>,
+) {
+    let big = died_events.iter().count() + cleared_events.iter().count() > 0;
+    if big && !reduced_motion.0 {
+        clock.freeze(HIT_STOP_TIME);
+    }
+}
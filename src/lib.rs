@@ -1,77 +1,377 @@
 #![allow(unused)] // silence warnings while dev // comment out later
+#![allow(clippy::type_complexity)] // bevy queries
+#![allow(clippy::too_many_arguments)] // bevy systems
 
+use animation::{AnimationClip, AnimationFinished, AnimationPlugin, PlayMode, SpriteAnimation};
+use background::BackgroundPlugin;
 use bevy::{
+    asset::AssetServerSettings,
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
-    ecs::entity,
+    ecs::{entity, schedule::ShouldRun},
     math::Vec3Swizzles,
     prelude::*,
-    sprite::collide_aabb::collide,
-    utils::HashSet,
+    render::camera::ScalingMode,
+    utils::{Duration, HashSet},
+    window::{WindowId, WindowResized},
 };
+use canvas::{CanvasPlugin, WindowSettings};
+use character::{CharacterPlugin, PlayerCharacter, PrimaryWeapon, ShipSprite, Special};
+use collision::{collision_detection_system, CollisionEvent, CollisionPlugin};
 use components::{
-    Enemy, Explosion, ExplosionTimer, ExplosionToSpawn, FromEnemy, FromPlayer, Laser, Movable,
-    Player, SpriteSize, Velocity,
+    Damage, DespawnPolicy, Enemy, EnemyArchetype, Explosion, FromEnemy, FromPlayer, Health, Hitbox,
+    HitboxShape, Laser, Movable, Owner, Piercing, Player, PlayerId, SpriteSize, Swept, Velocity,
 };
-use enemy::EnemyPlugin;
+use config::{ConfigPlugin, GameConfig};
+use controls::{ControlsPlugin, KeyBindings};
+use drone::DronePlugin;
+use enemy::{EnemyPlugin, Formation};
+use events::{
+    EnemyEscaped, EnemyKilled, EventsPlugin, ExplosionToSpawn, LaserImpact, PlayerDied, PlayerHit,
+    ShotFired, WaveCleared,
+};
+use graze::GrazePlugin;
+use highscore::HighScorePlugin;
+use juice::{CameraShake, JuicePlugin};
+use loading::{LoadingAssets, LoadingPlugin};
+use particle::ParticlePlugin;
 use player::PlayerPlugin;
+use pool::{EntityPool, PoolKind, PoolMember, PoolPlugin, Pooled};
+use powerup::PowerUpPlugin;
+use rand::{rngs::StdRng, SeedableRng};
+use score::ScorePlugin;
+use settings::{settings_menu_input_system, SettingsPlugin};
+use skin::SkinPlugin;
+use sound::SoundPlugin;
 use wasm_bindgen::prelude::*;
+use weapon::WeaponPlugin;
 
+mod animation;
+mod background;
+mod canvas;
+mod character;
+mod collision;
 mod components;
+mod config;
+mod controls;
+mod drone;
 mod enemy;
+mod events;
+mod graze;
+mod highscore;
+mod juice;
+mod loading;
+mod particle;
+mod platform;
 mod player;
+pub mod pool;
+mod powerup;
+mod score;
+mod settings;
+mod skin;
+mod sound;
+mod ui;
+mod weapon;
 
 // region: --- Asset constants
 const PLAYER_SPRITE: &str = "black_jesus.png";
-const PLAYER_SIZE: (f32, f32) = (144., 177.);
+const PLAYER_ALT_SPRITE: &str = "player_b_01.png"; // second ship
 const PLAYER_LASER_SPRITE: &str = "player_laser.png";
-const PLAYER_LASER_SIZE: (f32, f32) = (9., 9.);
-const PLAYER_RESPAWN_DELAY: f64 = 1.;
-
-const SPRITE_SCALE: f32 = 0.5;
 
 const ENEMY_SPRITE: &str = "enemy_b.png";
-const ENEMY_SIZE: (f32, f32) = (67., 67.);
 const ENEMY_LASER_SPRITE: &str = "enemy_laser.png";
-const ENEMY_LASER_SIZE: (f32, f32) = (15., 15.);
+// skin packs, the sprites above are the built in fallback
+const SKIN_DIR: &str = "skins";
+const BUILTIN_SKINS: &[&str] = &[
+    "skins/classic.skin.ron",
+    "skins/pixel.skin.ron",
+    "skins/retro.skin.ron",
+];
+const DEFAULT_SKIN: &str = "CLASSIC";
+
+// hitboxes, sizes in sprite pixels
+const PLAYER_HITBOX: Hitbox = Hitbox {
+    shape: HitboxShape::Mask,
+    offset: Vec2::ZERO,
+    scale: 1.,
+};
+const WING_HITBOX: Hitbox = Hitbox {
+    shape: HitboxShape::Circle { radius: 22. },
+    offset: Vec2::new(0., -4.),
+    scale: 1.,
+};
+const PLAYER_LASER_HITBOX: Hitbox = Hitbox {
+    shape: HitboxShape::Capsule {
+        half_length: 2.,
+        radius: 4.5,
+    },
+    offset: Vec2::ZERO,
+    scale: 1.,
+};
+const ENEMY_HITBOX: Hitbox = Hitbox {
+    shape: HitboxShape::Circle { radius: 30. },
+    offset: Vec2::new(0., 4.),
+    scale: 0.9,
+};
+const ENEMY_LASER_HITBOX: Hitbox = Hitbox {
+    shape: HitboxShape::Circle { radius: 7.5 },
+    offset: Vec2::ZERO,
+    scale: 0.8,
+};
 
 const EXPLOSION_SHEET: &str = "explo_a_sheet.png";
+
+const FONT: &str = "FiraSans-Bold.ttf";
+const GAME_CONFIG: &str = "game.config.ron"; // speeds, limits and sprite sizes
 const EXPLOSION_LEN: usize = 16;
+const EXPLOSION_CLIPS: &[AnimationClip] = &[AnimationClip {
+    name: "explode",
+    first: 0,
+    last: EXPLOSION_LEN - 1,
+    frame_time: 0.05,
+    mode: PlayMode::Once,
+}];
+
+const PLAYER_LASER_SFX: &str = "sounds/player_laser.wav";
+const ENEMY_LASER_SFX: &str = "sounds/enemy_laser.wav";
+const HIT_SFX: &str = "sounds/hit.wav";
+const EXPLOSION_SFX: &str = "sounds/explosion.wav";
+const PICKUP_SFX: &str = "sounds/pickup.wav";
+const PLAYER_DEATH_SFX: &str = "sounds/player_death.wav";
+// music stems, one per layer (base, drums, lead, high), all loop in sync
+const TITLE_MUSIC: &[&str] = &["sounds/title_base.wav"];
+const GAMEPLAY_MUSIC: &[&str] = &[
+    "sounds/gameplay_base.wav",
+    "sounds/gameplay_drums.wav",
+    "sounds/gameplay_lead.wav",
+    "sounds/gameplay_high.wav",
+];
 
 // endregion: --- Asset constants
 
 // region: --- Game constants
+const VIRTUAL_WIDTH: f32 = 1280.; // play area in world units, scaled to the window
+const VIRTUAL_HEIGHT: f32 = 720.;
+const WINDOW_SAVE_DELAY: f64 = 0.5; // seconds without window changes before saving
 const TIME_STEP: f32 = 1. / 60.;
-const BASE_SPEED: f32 = 300.;
-const ENEMY_MAX: u32 = 100;
-const FORMATION_MEMBERS_MAX: u32 = 10;
+const PROJECTILE_DESPAWN_MARGIN: f32 = 50.;
+const ENEMY_DESPAWN_MARGIN: f32 = 120.;
+const ENEMY_LOST_MARGIN: f32 = 400.; // spawns 100 outside, further out it is not coming back
+const ENEMY_FLEE_AFTER: (f32, f32) = (15., 30.); // seconds a formation stays, min/max
+const FORMATIONS_PER_WAVE: u32 = 5;
+const COMBO_WINDOW: f64 = 1.5; // seconds between kills to keep the combo going
+const COMBO_STEP: f32 = 0.1; // multiplier gain per combo kill
+const COMBO_MAX: f32 = 8.;
+const COMBO_DECAY: f32 = 1.; // multiplier lost per second without kills
+const GROUP_CLEAR_BONUS: u64 = 1000;
+const SCORE_POPUP_TIME: f32 = 0.8;
+const GRAZE_MARGIN: f32 = 24.; // around the ship sprite
+const GRAZE_POINTS: u64 = 10;
+const GRAZE_METER_GAIN: f32 = 0.02;
+const GRAZE_SPARK_TIME: f32 = 0.25;
+// px per second, faster projectiles use swept collision
+const SWEPT_SPEED_MIN: f32 = 250.;
+const PLAYER_LIVES: u32 = 3;
+const MAX_PLAYERS: usize = 2; // local co-op
+
+// keyboard halves, gamepads work on top
+const PLAYER_KEYS: [KeyBindings; MAX_PLAYERS] = [
+    KeyBindings {
+        left: KeyCode::Left,
+        right: KeyCode::Right,
+        fire: KeyCode::Space,
+        special: KeyCode::LShift,
+        secondary: KeyCode::LControl,
+    },
+    KeyBindings {
+        left: KeyCode::A,
+        right: KeyCode::D,
+        fire: KeyCode::F,
+        special: KeyCode::G,
+        secondary: KeyCode::H,
+    },
+];
+const STICK_DEADZONE: f32 = 0.2;
+const COOP_SPAWN_SPREAD: f32 = 160.; // px between co-op ships on spawn
+const REVIVE_KILLS: u32 = 25; // team kills to bring back a player out of lives
+
+// ships on the select screen, lives are for normal difficulty
+const CHARACTERS: &[PlayerCharacter] = &[
+    PlayerCharacter {
+        name: "BLACK JESUS",
+        key: "player",
+        sprite: ShipSprite::Main,
+        hitbox: PLAYER_HITBOX,
+        speed: 1.,
+        weapon: PrimaryWeapon::Twin,
+        special: Special::Bomb,
+        lives: PLAYER_LIVES,
+    },
+    PlayerCharacter {
+        name: "WING",
+        key: "wing",
+        sprite: ShipSprite::Alt,
+        hitbox: WING_HITBOX,
+        speed: 1.4,
+        weapon: PrimaryWeapon::Spread,
+        special: Special::Dash,
+        lives: PLAYER_LIVES - 1,
+    },
+];
+const BOMB_COOLDOWN: f64 = 8.; // seconds
+const DASH_COOLDOWN: f64 = 1.5;
+const DASH_TIME: f64 = 0.2;
+const DASH_SPEED: f32 = 3.; // times the ship speed
+const SPREAD_ANGLE: f32 = 0.25; // sideways velocity of the outer spread lasers
+const GAME_OVER_DELAY: f64 = 1.5; // seconds after the last death
+const HIGH_SCORE_ENTRIES: usize = 10;
+const SFX_MAX_COPIES: usize = 4; // same sound playing at once
+const MUSIC_CROSSFADE: f32 = 1.5; // seconds
+const MUSIC_BAR_LEN: f64 = 2.; // 4 beats at 120 bpm, layers switch on bar lines
+const MUSIC_LAYER_FADE: f32 = 0.5; // seconds
+const MUSIC_DRUMS_ENEMIES: u32 = 10; // alive enemies to bring in the drums
+const MUSIC_HIGH_ENEMIES: u32 = 40;
+const MUSIC_LEAD_COMBO: f32 = 2.; // multiplier to bring in the lead
+const PARTICLE_BUDGET: usize = 1500; // alive at once
+const ENGINE_TRAIL_RATE: f32 = 60.; // particles per second
+const TRAUMA_KILL: f32 = 0.12; // camera trauma per event, capped at 1
+const TRAUMA_HIT: f32 = 0.5;
+const TRAUMA_DEATH: f32 = 0.8;
+const TRAUMA_GROUP_CLEAR: f32 = 0.4;
+const TRAUMA_DECAY: f32 = 1.5; // per second
+const SHAKE_MAX_OFFSET: f32 = 24.;
+const SHAKE_MAX_ANGLE: f32 = 0.05; // radians
+const HIT_STOP_TIME: f32 = 0.08; // seconds
+const CHARGE_TIME: f64 = 1.2; // seconds of holding fire for a full charge
+const CHARGE_MIN: f32 = 0.35; // share of a full charge that fires a beam on release
+const BEAM_DAMAGE: u32 = 8;
+const BEAM_SPEED: f32 = 3.; // times base_speed
+const BEAM_SIZE: (f32, f32) = (24., 240.); // full charge, px
+const MISSILE_COOLDOWN: f64 = 0.6; // seconds
+const MISSILE_DAMAGE: u32 = 2;
+const MISSILE_SPEED: f32 = 1.6; // times base_speed
+const MISSILE_TURN_RATE: f32 = 4.; // radians per second
+const POWER_UP_DROP_CHANCE: f64 = 0.05; // per kill
+const POWER_UP_SPEED: f32 = 0.35; // falling, times base_speed
+const POWER_UP_SIZE: f32 = 18.;
+const DRONE_MAX: usize = 4; // per player
+const DRONE_SIZE: f32 = 14.;
+const DRONE_TRAIL_SPACING: usize = 12; // recorded player positions between two drones
+const DRONE_ORBIT_RADIUS: f32 = 70.;
+const DRONE_ORBIT_SPEED: f32 = 2.5; // radians per second
+const DRONE_FOLLOW: f32 = 0.3; // share of the way to its spot covered per frame
+const BG_SCROLL_SPEED: f32 = 40.; // px per second, nearest star layer
+const BG_SCROLL_PER_WAVE: f32 = 8.;
+const BG_SCROLL_MAX: f32 = 200.;
 // endregion: --- Game constants
 
 // region: --- Resources
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AppState {
+    Loading,   // until every asset is in
+    LoadError, // dead end, names the broken files
+    CharacterSelect,
+    Playing,
+    GameOver, // name entry
+    Leaderboard,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameMode {
+    Arcade,
+    CoOp,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Arcade, GameMode::CoOp];
+
+    /// stable key for saved data
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameMode::Arcade => "arcade",
+            GameMode::CoOp => "coop",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Arcade => "ARCADE",
+            GameMode::CoOp => "CO-OP",
+        }
+    }
+}
+
+// seeded per game, all gameplay randomness goes through here
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
 pub struct WinSize {
     pub w: f32,
     pub h: f32,
 }
 
+// playable area in world coordinates, follows the window size
+pub struct ArenaBounds {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl ArenaBounds {
+    pub fn from_win_size(win_size: &WinSize) -> Self {
+        let half = Vec2::new(win_size.w, win_size.h) / 2.;
+        Self {
+            min: -half,
+            max: half,
+        }
+    }
+
+    /// true if pos is inside the arena grown by margin on every side
+    pub fn contains(&self, pos: Vec2, margin: f32) -> bool {
+        pos.cmpge(self.min - margin).all() && pos.cmple(self.max + margin).all()
+    }
+}
+
 pub struct GameTextures {
     player: Handle<Image>,
+    player_alt: Handle<Image>,
     player_laser: Handle<Image>,
     enemy: Handle<Image>,
     enemy_laser: Handle<Image>,
     explosion: Handle<TextureAtlas>,
 }
 
+pub struct GameFonts {
+    main: Handle<Font>,
+}
+
 pub struct EnemyCount(u32);
 pub struct PlayerState {
+    joined: bool,   // plays this game
     on: bool,       // is alive
     last_shot: f64, // -1 if not shot
+    lives: u32,
+    revive_kills: u32, // team kills since running out of lives
 }
 
 //how to apply a default for PlayerState
 impl Default for PlayerState {
     fn default() -> Self {
         Self {
+            joined: false,
             on: false,
             last_shot: -1.,
+            lives: PLAYER_LIVES,
+            revive_kills: 0,
         }
     }
 }
@@ -86,89 +386,287 @@ impl PlayerState {
         self.on = true;
         self.last_shot = -1.;
     }
+    /// joined but out of lives, waits for a revive
+    pub fn out(&self) -> bool {
+        self.joined && !self.on && self.lives == 0
+    }
+}
+
+// one PlayerState per PlayerId
+#[derive(Default)]
+pub struct PlayerStates(pub [PlayerState; MAX_PLAYERS]);
+
+impl PlayerStates {
+    pub fn joined(&self) -> impl Iterator<Item = &PlayerState> {
+        self.0.iter().filter(|state| state.joined)
+    }
 }
 pub struct KillCount(u32);
+
+// wave currently spawning (0 based)
+pub struct Wave(u32);
+
+// -Ressource - game time, stands still during hit-stop and while the settings menu
+// is open. gameplay reads this instead of Time so a freeze or a pause doesn't eat
+// into cooldowns, delays and timers
+#[derive(Default)]
+pub struct GameClock {
+    elapsed: f64,
+    delta: f32,
+    stopped: bool,
+    paused: bool,
+    frozen_for: f32, // real seconds of hit-stop left
+}
+
+impl GameClock {
+    /// game seconds so far, stands in for Time::seconds_since_startup
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+    /// 0 while stopped
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta
+    }
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f32(self.delta)
+    }
+    pub fn stopped(&self) -> bool {
+        self.stopped
+    }
+    /// 0 while stopped, 1 otherwise, for the per frame movement
+    pub fn scale(&self) -> f32 {
+        if self.stopped {
+            0.
+        } else {
+            1.
+        }
+    }
+    /// hit-stop, starts with the next frame
+    pub fn freeze(&mut self, seconds: f32) {
+        self.frozen_for = self.frozen_for.max(seconds);
+    }
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+}
 // endregion: --- Resource
 
+/// gameplay run criteria, chain behind other criteria (fixed timesteps, ...)
+/// also holds back while the game clock stands still
+pub fn playing_criteria(
+    In(should_run): In<ShouldRun>,
+    state: Res<State<AppState>>,
+    clock: Res<GameClock>,
+) -> ShouldRun {
+    if *state.current() == AppState::Playing && !clock.stopped() {
+        should_run
+    } else {
+        ShouldRun::No
+    }
+}
+
 fn main() {
+    let window_settings = WindowSettings::load();
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
         .insert_resource(WindowDescriptor {
             title: "Black Jesus vs. respawning Hitlers".to_string(),
-            width: 1280.0,
-            height: 720.0,
+            ..window_settings.descriptor()
+        })
+        .insert_resource(window_settings)
+        // hot reload for the config file, the browser can't watch files
+        .insert_resource(AssetServerSettings {
+            watch_for_changes: cfg!(not(target_arch = "wasm32")),
             ..Default::default()
         })
         .add_plugins(DefaultPlugins)
+        .add_plugin(ConfigPlugin)
         .add_plugin(LogDiagnosticsPlugin::default())
         .add_plugin(FrameTimeDiagnosticsPlugin::default())
+        .add_plugin(EventsPlugin)
+        .add_plugin(ControlsPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugin(EnemyPlugin)
+        .add_plugin(PoolPlugin)
+        .add_plugin(ScorePlugin)
+        .add_plugin(GrazePlugin)
+        .add_plugin(HighScorePlugin)
+        .add_plugin(SoundPlugin)
+        .add_plugin(ParticlePlugin)
+        .add_plugin(JuicePlugin)
+        .add_plugin(BackgroundPlugin)
+        .add_plugin(AnimationPlugin)
+        .add_plugin(CanvasPlugin)
+        .add_plugin(CollisionPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(LoadingPlugin)
+        .add_plugin(SkinPlugin)
+        .add_plugin(CharacterPlugin)
+        .add_plugin(PowerUpPlugin)
+        .add_plugin(DronePlugin)
+        .add_plugin(WeaponPlugin)
+        .add_state(AppState::Loading)
+        .insert_resource(GameMode::Arcade)
+        .insert_resource(GameRng::new(platform::unix_millis()))
+        .insert_resource(GameClock::default())
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            game_clock_system.after(settings_menu_input_system),
+        )
         .add_startup_system(setup_system)
-        .add_system(movable_system)
-        .add_system(player_laser_hit_enemy_system)
-        .add_system(enemy_laser_hit_player_system)
-        .add_system(explosion_to_spawn_system)
-        .add_system(explosion_animation_system)
+        .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(game_reset_system))
+        .add_system_set(
+            SystemSet::on_update(AppState::Playing)
+                .with_system(movable_system.before(collision_detection_system))
+                .with_system(despawn_policy_system.after(movable_system))
+                .with_system(player_laser_hit_enemy_system.after(collision_detection_system))
+                .with_system(enemy_laser_hit_player_system.after(collision_detection_system))
+                .with_system(enemy_count_system)
+                .with_system(kill_count_system)
+                .with_system(explosion_to_spawn_system),
+        )
+        .add_system(explosion_finished_system)
         .run();
 }
 
 fn setup_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
     mut texture_atlasses: ResMut<Assets<TextureAtlas>>,
-    mut windows: ResMut<Windows>,
 ) {
-    // - Camera
-    commands.spawn_bundle(Camera2dBundle::default()); //https://bevyengine.org/learn/book/migration-guides/0.7-0.8/
-
-    //capture window size
-    let window = windows.get_primary_mut().unwrap();
-    let (win_w, win_h) = (window.width(), window.height());
-    //position window
-    window.set_position(IVec2::new(2780, 4900));
-    // size window
-    let win_size = WinSize { w: win_w, h: win_h };
+    // - Camera, kept at the default z but seeing 100 further down for the background layers
+    let mut camera = Camera2dBundle::new_with_far(1100.); //https://bevyengine.org/learn/book/migration-guides/0.7-0.8/
+    camera.transform.translation.z = 999.9;
+    // always shows the whole virtual canvas, the viewport keeps the aspect
+    camera.projection.scaling_mode = ScalingMode::Auto {
+        min_width: VIRTUAL_WIDTH,
+        min_height: VIRTUAL_HEIGHT,
+    };
+    commands.spawn_bundle(camera).insert(CameraShake::default());
+
+    // play area is the virtual canvas, whatever the window size
+    let win_size = WinSize {
+        w: VIRTUAL_WIDTH,
+        h: VIRTUAL_HEIGHT,
+    };
+    commands.insert_resource(ArenaBounds::from_win_size(&win_size));
     commands.insert_resource(win_size);
 
     //create explosion texture
-    let texture_handle = asset_server.load(EXPLOSION_SHEET);
+    let texture_handle = loading.load(&asset_server, EXPLOSION_SHEET);
     let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(64., 64.), 4, 4);
     let explosion = texture_atlasses.add(texture_atlas);
 
     // add GameTextures resource
     let game_textures = GameTextures {
-        player: asset_server.load(PLAYER_SPRITE),
-        player_laser: asset_server.load(PLAYER_LASER_SPRITE),
-        enemy: asset_server.load(ENEMY_SPRITE),
-        enemy_laser: asset_server.load(ENEMY_LASER_SPRITE),
+        player: loading.load(&asset_server, PLAYER_SPRITE),
+        player_alt: loading.load(&asset_server, PLAYER_ALT_SPRITE),
+        player_laser: loading.load(&asset_server, PLAYER_LASER_SPRITE),
+        enemy: loading.load(&asset_server, ENEMY_SPRITE),
+        enemy_laser: loading.load(&asset_server, ENEMY_LASER_SPRITE),
         explosion,
     };
     commands.insert_resource(game_textures);
+    commands.insert_resource(GameFonts {
+        main: loading.load(&asset_server, FONT),
+    });
     commands.insert_resource(EnemyCount(0));
     commands.insert_resource(KillCount(0));
+    commands.insert_resource(Wave(0));
 }
 
-fn movable_system(
+// fresh game, every plugin resets its own state on entering Playing
+fn game_reset_system(
     mut commands: Commands,
-    win_size: Res<WinSize>,
-    mut query: Query<(Entity, &Velocity, &mut Transform, &Movable)>,
+    mut pool: ResMut<EntityPool>,
+    mut game_rng: ResMut<GameRng>,
+    query: Query<Entity, Or<(With<Laser>, With<Explosion>, With<PoolMember>)>>,
 ) {
-    for (entity, velocity, mut transform, movable) in query.iter_mut() {
+    // pooled ones included, the pool starts over empty
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *pool = EntityPool::default();
+
+    *game_rng = GameRng::new(platform::unix_millis());
+    commands.insert_resource(EnemyCount(0));
+    commands.insert_resource(KillCount(0));
+    commands.insert_resource(Wave(0));
+}
+
+fn game_clock_system(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    let delta = time.delta_seconds();
+    clock.stopped = clock.paused || clock.frozen_for > 0.;
+    // a hit-stop left over when pausing runs out after the pause
+    if !clock.paused {
+        clock.frozen_for = (clock.frozen_for - delta).max(0.);
+    }
+    clock.delta = if clock.stopped { 0. } else { delta };
+    clock.elapsed += clock.delta as f64;
+}
+
+fn movable_system(
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    mut query: Query<
+        (&Velocity, &mut Transform, Option<&mut Swept>),
+        (With<Movable>, Without<Pooled>),
+    >,
+) {
+    for (velocity, mut transform, swept) in query.iter_mut() {
+        if let Some(mut swept) = swept {
+            swept.prev = transform.translation;
+        }
+
         let translation = &mut transform.translation;
-        translation.x += velocity.x * TIME_STEP * BASE_SPEED;
-        translation.y += velocity.y * TIME_STEP * BASE_SPEED;
-
-        if (movable.auto_despawn) {
-            // --- despwan lasers out of screen
-            const MARGIN: f32 = 230.;
-
-            if translation.y > win_size.h / 2. + MARGIN
-                || translation.y < -win_size.h / 2. - MARGIN
-                || translation.x > win_size.h / 2. + MARGIN
-                || translation.x < -win_size.h / 2. - MARGIN
-            {
-                commands.entity(entity).despawn();
+        translation.x += velocity.x * TIME_STEP * config.base_speed * clock.scale();
+        translation.y += velocity.y * TIME_STEP * config.base_speed * clock.scale();
+    }
+}
+
+fn despawn_policy_system(
+    mut commands: Commands,
+    arena: Res<ArenaBounds>,
+    mut pool: ResMut<EntityPool>,
+    mut escaped_events: EventWriter<EnemyEscaped>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut DespawnPolicy,
+            Option<&PoolMember>,
+            Option<(&EnemyArchetype, &Formation)>,
+        ),
+        Without<Pooled>,
+    >,
+) {
+    for (entity, transform, mut policy, pool_member, enemy) in query.iter_mut() {
+        let pos = transform.translation.truncate();
+
+        let gone = match &mut *policy {
+            DespawnPolicy::OffScreen { margin } => !arena.contains(pos, *margin),
+            DespawnPolicy::AfterEntering { margin, entered } => {
+                if !*entered {
+                    *entered = arena.contains(pos, 0.);
+                    // never made it in, e.g. its formation fled on the way
+                    !*entered && !arena.contains(pos, ENEMY_LOST_MARGIN)
+                } else {
+                    !arena.contains(pos, *margin)
+                }
+            }
+        };
+
+        if gone {
+            pool.despawn(&mut commands, entity, pool_member);
+            // enemy left the stage alive
+            if let Some((archetype, formation)) = enemy {
+                escaped_events.send(EnemyEscaped {
+                    archetype: *archetype,
+                    position: transform.translation,
+                    group: formation.group,
+                    wave: formation.wave,
+                });
             }
         }
     }
@@ -176,147 +674,174 @@ fn movable_system(
 
 fn enemy_laser_hit_player_system(
     mut commands: Commands,
-    mut player_state: ResMut<PlayerState>,
-    time: Res<Time>,
-    laser_query: Query<(
-        Entity,
-        &Transform,
-        &SpriteSize,
-        (With<Laser>, With<FromEnemy>),
-    )>,
-    player_query: Query<(Entity, &Transform, &SpriteSize), With<Player>>,
+    mut pool: ResMut<EntityPool>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut player_hit_events: EventWriter<PlayerHit>,
+    mut impact_events: EventWriter<LaserImpact>,
+    laser_query: Query<(&Owner, &Transform, Option<&PoolMember>), (With<Laser>, With<FromEnemy>)>,
+    player_query: Query<&Transform, With<Player>>,
 ) {
-    if let Ok((player_entity, player_tf, player_size)) = player_query.get_single() {
-        let player_scale = Vec2::from(player_tf.scale.xy());
-
-        for (laser_entity, laser_tf, laser_size, _) in laser_query.iter() {
-            let laser_scale = Vec2::from(laser_tf.scale.xy());
-
-            //check for collision
-            let collision = collide(
-                laser_tf.translation,
-                laser_size.0 * laser_scale,
-                player_tf.translation,
-                player_size.0 * player_scale,
-            );
-
-            // perform collision
-            //if let Some(_) = collision {
-            // remove player
-            //commands.entity(player_entity).despawn();
-            //player_state.shot(time.seconds_since_startup());
-            // remove laser
-            //commands.entity(laser_entity).despawn();
+    let mut despawned_entities: HashSet<Entity> = HashSet::new();
 
-            //create explosion at player
-            //commands
-            //    .spawn()
-            //    .insert(ExplosionToSpawn(player_tf.translation.clone()));
+    for event in collision_events.iter() {
+        let (laser_entity, player_entity) =
+            match event.ordered(|entity| laser_query.contains(entity)) {
+                Some(pair) => pair,
+                None => continue,
+            };
+        if despawned_entities.contains(&laser_entity) {
+            continue;
+        }
 
-            //breaks gameloop
-            //break;
-            //}
+        if let (Ok((owner, laser_tf, laser_member)), Ok(player_tf)) = (
+            laser_query.get(laser_entity),
+            player_query.get(player_entity),
+        ) {
+            // remove laser
+            pool.despawn(&mut commands, laser_entity, laser_member);
+            despawned_entities.insert(laser_entity);
+
+            impact_events.send(LaserImpact {
+                position: laser_tf.translation,
+            });
+            player_hit_events.send(PlayerHit {
+                player: player_entity,
+                position: player_tf.translation,
+                by: owner.0,
+            });
         }
     }
 }
 
 fn player_laser_hit_enemy_system(
     mut commands: Commands,
-    mut enemy_count: ResMut<EnemyCount>,
-    mut kill_count: ResMut<KillCount>,
-    laser_query: Query<(
-        Entity,
-        &Transform,
-        &SpriteSize,
+    mut pool: ResMut<EntityPool>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut enemy_killed_events: EventWriter<EnemyKilled>,
+    mut explosion_events: EventWriter<ExplosionToSpawn>,
+    mut impact_events: EventWriter<LaserImpact>,
+    mut laser_query: Query<
+        (
+            &Owner,
+            &PlayerId,
+            &Transform,
+            Option<&Damage>,
+            Option<&mut Piercing>,
+            Option<&PoolMember>,
+        ),
         (With<Laser>, With<FromPlayer>),
-    )>,
-    enemy_query: Query<(Entity, &Transform, &SpriteSize), With<Enemy>>,
+    >,
+    mut enemy_query: Query<(&Transform, &EnemyArchetype, &Formation, &mut Health), With<Enemy>>,
 ) {
     // helper avoids despawning multiple times
     // cross check against another set to avoid double destroy on no longer existing entity
     let mut despawned_entities: HashSet<Entity> = HashSet::new();
 
-    // iteratre through lasers
-    for (laser_entity, laser_tf, laser_size, _) in laser_query.iter() {
-        if despawned_entities.contains(&laser_entity) {
+    for event in collision_events.iter() {
+        let (laser_entity, enemy_entity) =
+            match event.ordered(|entity| laser_query.contains(entity)) {
+                Some(pair) => pair,
+                None => continue,
+            };
+        if despawned_entities.contains(&enemy_entity) || despawned_entities.contains(&laser_entity)
+        {
             continue;
         }
 
-        //let laser_scale = Vec2::from(laser_tf.scale.xy());
-        let laser_scale: Vec2 = Vec2::from(laser_tf.scale.xy());
-        // iterate through enemies
-        for (enemy_entity, enemy_tf, enemy_size) in enemy_query.iter() {
-            if despawned_entities.contains(&enemy_entity)
-                || despawned_entities.contains(&laser_entity)
-            {
-                continue;
+        if let (
+            Ok((owner, player, laser_tf, damage, piercing, laser_member)),
+            Ok((enemy_tf, archetype, formation, mut health)),
+        ) = (
+            laser_query.get_mut(laser_entity),
+            enemy_query.get_mut(enemy_entity),
+        ) {
+            match piercing {
+                // beams keep going, but only hurt each enemy once
+                Some(mut piercing) => {
+                    if !piercing.0.insert(enemy_entity) {
+                        continue;
+                    }
+                }
+                //remove laser
+                None => {
+                    pool.despawn(&mut commands, laser_entity, laser_member);
+                    despawned_entities.insert(laser_entity);
+                }
             }
 
-            let enemy_scale = Vec2::from(enemy_tf.scale.xy());
-
-            // introducing collided
-            // --collision logic
-            let collision = collide(
-                laser_tf.translation,
-                laser_size.0 * laser_scale,
-                enemy_tf.translation,
-                enemy_size.0 * enemy_scale,
-            );
-
-            // perform collision, if collision
-            if let Some(_) = collision {
-                //remove enemy entity using despawn
-                commands.entity(enemy_entity).despawn();
-                despawned_entities.insert(enemy_entity);
-                enemy_count.0 -= 1;
-                kill_count.0 += 1;
-                //remove laser
-                commands.entity(laser_entity).despawn();
-                despawned_entities.insert(laser_entity);
-                //spwan explosion
-                commands
-                    .spawn()
-                    .insert(ExplosionToSpawn(enemy_tf.translation.clone()));
+            impact_events.send(LaserImpact {
+                position: laser_tf.translation,
+            });
+            health.0 = health.0.saturating_sub(damage.map_or(1, |damage| damage.0));
+            if health.0 > 0 {
+                continue;
             }
+
+            //remove enemy entity using despawn
+            commands.entity(enemy_entity).despawn();
+            despawned_entities.insert(enemy_entity);
+
+            enemy_killed_events.send(EnemyKilled {
+                archetype: *archetype,
+                position: enemy_tf.translation,
+                by: owner.0,
+                player: *player,
+                group: formation.group,
+                wave: formation.wave,
+            });
+            //spwan explosion
+            explosion_events.send(ExplosionToSpawn(enemy_tf.translation));
         }
     }
 }
 
+fn enemy_count_system(
+    mut enemy_count: ResMut<EnemyCount>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut escaped_events: EventReader<EnemyEscaped>,
+) {
+    let removed = killed_events.iter().count() + escaped_events.iter().count();
+    enemy_count.0 = enemy_count.0.saturating_sub(removed as u32);
+}
+
+fn kill_count_system(
+    mut kill_count: ResMut<KillCount>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    kill_count.0 += killed_events.iter().count() as u32;
+}
+
 fn explosion_to_spawn_system(
     mut commands: Commands,
     game_textures: Res<GameTextures>,
-    query: Query<(Entity, &ExplosionToSpawn)>,
+    mut pool: ResMut<EntityPool>,
+    mut explosion_events: EventReader<ExplosionToSpawn>,
 ) {
-    for (explosion_spawn_entity, explosion_to_spawn) in query.iter() {
-        commands
-            .spawn_bundle(SpriteSheetBundle {
-                texture_atlas: game_textures.explosion.clone(),
-                transform: Transform {
-                    translation: explosion_to_spawn.0,
-                    ..Default::default()
-                },
+    for explosion_to_spawn in explosion_events.iter() {
+        let bundle = SpriteSheetBundle {
+            texture_atlas: game_textures.explosion.clone(),
+            transform: Transform {
+                translation: explosion_to_spawn.0,
                 ..Default::default()
-            })
+            },
+            ..Default::default()
+        };
+        pool.acquire(&mut commands, PoolKind::Explosion, bundle)
             .insert(Explosion)
-            .insert(ExplosionTimer::default());
-
-        // despawn the explosionToDespawn
-        commands.entity(explosion_spawn_entity).despawn();
+            .insert(SpriteAnimation::new(EXPLOSION_CLIPS));
     }
 }
 
-fn explosion_animation_system(
+// back to the pool once played through
+fn explosion_finished_system(
     mut commands: Commands,
-    time: Res<Time>,
-    mut query: Query<(Entity, &mut ExplosionTimer, &mut TextureAtlasSprite), With<Explosion>>,
+    mut pool: ResMut<EntityPool>,
+    mut finished_events: EventReader<AnimationFinished>,
+    query: Query<(), (With<Explosion>, Without<Pooled>)>,
 ) {
-    for (entity, mut timer, mut sprite) in query.iter_mut() {
-        timer.0.tick(time.delta());
-        if timer.0.finished() {
-            sprite.index += 1; // how to move to next on sprite (textureatlas) index
-            if sprite.index >= EXPLOSION_LEN {
-                commands.entity(entity).despawn();
-            }
+    for event in finished_events.iter() {
+        if query.contains(event.entity) {
+            pool.release(&mut commands, event.entity, PoolKind::Explosion);
         }
     }
 }
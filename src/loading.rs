@@ -0,0 +1,190 @@
+use bevy::{
+    asset::{Asset, LoadState},
+    prelude::*,
+};
+
+use crate::{
+    ui::{despawn_screen, font_label, screen_root},
+    AppState,
+};
+
+// built into the binary, the loading and error screens can't wait on a font
+// that might be the broken file
+const FALLBACK_FONT: &[u8] = include_bytes!("../assets/FiraSans-Bold.ttf");
+
+// -- nothing plays before every asset is in, broken or missing files stop on an error screen
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LoadingAssets::default())
+            .insert_resource(LoadErrors::default())
+            .add_startup_system(fallback_font_system)
+            .add_system_set(
+                SystemSet::on_enter(AppState::Loading).with_system(loading_setup_system),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Loading).with_system(loading_progress_system),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Loading).with_system(despawn_screen::<LoadingUi>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::LoadError).with_system(load_error_setup_system),
+            );
+    }
+}
+
+// -Ressource - every handle the game needs before it can start, with its path
+#[derive(Default)]
+pub struct LoadingAssets {
+    handles: Vec<(String, HandleUntyped)>,
+    pending: usize, // assets only known once others are in, e.g. the images of a skin pack
+}
+
+impl LoadingAssets {
+    /// asset_server.load that the loading screen waits for
+    pub fn load<T: Asset>(&mut self, asset_server: &AssetServer, path: &str) -> Handle<T> {
+        let handle = asset_server.load(path);
+        self.track(path, handle.clone_untyped());
+        handle
+    }
+
+    /// waits for a handle that was loaded some other way (folders, ...)
+    pub fn track(&mut self, path: impl Into<String>, handle: HandleUntyped) {
+        self.handles.push((path.into(), handle));
+    }
+
+    /// keeps the loading screen up until `resolve` was called as often
+    pub fn expect(&mut self, count: usize) {
+        self.pending += count;
+    }
+
+    pub fn resolve(&mut self) {
+        self.pending = self.pending.saturating_sub(1);
+    }
+}
+
+// -Ressource - the embedded font
+struct FallbackFont(Handle<Font>);
+
+// -Ressource - paths that failed, shown on the error screen
+#[derive(Default)]
+struct LoadErrors(Vec<String>);
+
+// region: --- Components
+#[derive(Component)]
+struct LoadingUi;
+
+#[derive(Component)]
+struct LoadingBar;
+
+#[derive(Component)]
+struct LoadErrorUi;
+// endregion: --- Components
+
+fn fallback_font_system(mut commands: Commands, mut fonts: ResMut<Assets<Font>>) {
+    let font = Font::try_from_bytes(FALLBACK_FONT.to_vec()).expect("embedded font is valid");
+    commands.insert_resource(FallbackFont(fonts.add(font)));
+}
+
+fn loading_setup_system(mut commands: Commands, font: Res<FallbackFont>) {
+    commands
+        .spawn_bundle(screen_root())
+        .insert(LoadingUi)
+        .with_children(|parent| {
+            parent.spawn_bundle(font_label(font.0.clone(), "LOADING", 36., Color::WHITE));
+            // bar frame, the fill grows with the share of loaded assets
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(400.), Val::Px(16.)),
+                        margin: UiRect::all(Val::Px(6.)),
+                        ..Default::default()
+                    },
+                    color: Color::rgb(0.2, 0.2, 0.2).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..Default::default()
+                            },
+                            color: Color::rgb(1., 0.85, 0.3).into(),
+                            ..Default::default()
+                        })
+                        .insert(LoadingBar);
+                });
+        });
+}
+
+fn loading_progress_system(
+    asset_server: Res<AssetServer>,
+    loading: Res<LoadingAssets>,
+    mut errors: ResMut<LoadErrors>,
+    mut state: ResMut<State<AppState>>,
+    mut bar_query: Query<&mut Style, With<LoadingBar>>,
+) {
+    let mut loaded = 0;
+    let mut failed = Vec::new();
+    for (path, handle) in loading.handles.iter() {
+        match asset_server.get_load_state(handle) {
+            LoadState::Loaded => loaded += 1,
+            LoadState::Failed => failed.push(path.clone()),
+            _ => {}
+        }
+    }
+
+    let total = loading.handles.len() + loading.pending;
+    let progress = loaded as f32 / total.max(1) as f32;
+    for mut style in bar_query.iter_mut() {
+        style.size.width = Val::Percent(progress * 100.);
+    }
+
+    // wait for everything to settle, then report every broken file at once
+    if !failed.is_empty() && loaded + failed.len() == loading.handles.len() {
+        for path in failed.iter() {
+            error!("could not load asset {}", path);
+        }
+        errors.0 = failed;
+        let _ = state.set(AppState::LoadError);
+    } else if loaded == total {
+        let _ = state.set(AppState::CharacterSelect);
+    }
+}
+
+// stays up, there is nothing to play without the assets
+fn load_error_setup_system(
+    mut commands: Commands,
+    font: Res<FallbackFont>,
+    errors: Res<LoadErrors>,
+) {
+    commands
+        .spawn_bundle(screen_root())
+        .insert(LoadErrorUi)
+        .with_children(|parent| {
+            parent.spawn_bundle(font_label(
+                font.0.clone(),
+                "COULD NOT LOAD",
+                36.,
+                Color::rgb(1., 0.3, 0.3),
+            ));
+            for path in errors.0.iter() {
+                parent.spawn_bundle(font_label(
+                    font.0.clone(),
+                    format!("assets/{}", path),
+                    22.,
+                    Color::WHITE,
+                ));
+            }
+            parent.spawn_bundle(font_label(
+                font.0.clone(),
+                "missing or broken files, check the assets folder",
+                18.,
+                Color::GRAY,
+            ));
+        });
+}
@@ -0,0 +1,256 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    events::{EnemyKilled, LaserImpact, PlayerDied},
+    pool::{EntityPool, PoolKind, Pooled},
+    GameClock, PARTICLE_BUDGET,
+};
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ParticleBudget(PARTICLE_BUDGET))
+            .add_system(particle_from_events_system.before(particle_emitter_system))
+            .add_system(particle_emitter_system)
+            .add_system(particle_update_system);
+    }
+}
+
+// what a single emitted particle looks like and how it moves
+#[derive(Clone, Copy)]
+pub struct ParticleEffect {
+    pub lifetime: (f32, f32), // seconds, min/max
+    pub speed: (f32, f32),
+    pub direction: f32, // radians, 0 is +x
+    pub spread: f32,    // radians around direction, 2 PI for all around
+    pub gravity: Vec2,
+    pub drag: f32,             // velocity lost per second (fraction)
+    pub color: (Color, Color), // start/end of life
+    pub size: (f32, f32),      // start/end of life
+}
+
+impl ParticleEffect {
+    pub const ENEMY_DEBRIS: ParticleEffect = ParticleEffect {
+        lifetime: (0.4, 0.9),
+        speed: (80., 260.),
+        direction: 0.,
+        spread: 2. * PI,
+        gravity: Vec2::new(0., -300.),
+        drag: 1.5,
+        color: (Color::rgb(1., 0.8, 0.3), Color::rgba(0.6, 0.1, 0., 0.)),
+        size: (6., 2.),
+    };
+
+    pub const PLAYER_DEBRIS: ParticleEffect = ParticleEffect {
+        lifetime: (0.6, 1.4),
+        speed: (60., 320.),
+        direction: 0.,
+        spread: 2. * PI,
+        gravity: Vec2::new(0., -200.),
+        drag: 1.,
+        color: (Color::rgb(0.8, 0.9, 1.), Color::rgba(0.2, 0.3, 1., 0.)),
+        size: (8., 2.),
+    };
+
+    pub const LASER_SPARKS: ParticleEffect = ParticleEffect {
+        lifetime: (0.1, 0.3),
+        speed: (120., 300.),
+        direction: 0.,
+        spread: 2. * PI,
+        gravity: Vec2::ZERO,
+        drag: 4.,
+        color: (Color::WHITE, Color::rgba(1., 0.9, 0.4, 0.)),
+        size: (3., 1.),
+    };
+
+    pub const ENGINE_TRAIL: ParticleEffect = ParticleEffect {
+        lifetime: (0.2, 0.4),
+        speed: (100., 180.),
+        direction: -PI / 2.,
+        spread: 0.3,
+        gravity: Vec2::ZERO,
+        drag: 0.5,
+        color: (Color::rgb(1., 0.7, 0.2), Color::rgba(1., 0.2, 0., 0.)),
+        size: (7., 1.),
+    };
+}
+
+#[derive(Clone, Copy)]
+pub enum EmitMode {
+    Burst { count: u32 },     // all at once, the emitter entity goes away after
+    Continuous { rate: f32 }, // particles per second
+}
+
+// -Ressource - max particles alive at once, emitters skip what doesn't fit
+pub struct ParticleBudget(pub usize);
+
+// region: --- Components
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: ParticleEffect,
+    pub mode: EmitMode,
+    pub offset: Vec2, // from the entity, world space
+    carry: f32,       // fractional particles left from the last frame
+}
+
+impl ParticleEmitter {
+    pub fn burst(effect: ParticleEffect, count: u32) -> Self {
+        Self {
+            effect,
+            mode: EmitMode::Burst { count },
+            offset: Vec2::ZERO,
+            carry: 0.,
+        }
+    }
+
+    pub fn continuous(effect: ParticleEffect, rate: f32, offset: Vec2) -> Self {
+        Self {
+            effect,
+            mode: EmitMode::Continuous { rate },
+            offset,
+            carry: 0.,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    gravity: Vec2,
+    drag: f32,
+    color: (Color, Color),
+    size: (f32, f32),
+}
+// endregion: --- Components
+
+/// standalone burst at a position
+pub fn spawn_burst(commands: &mut Commands, effect: ParticleEffect, count: u32, position: Vec3) {
+    commands
+        .spawn()
+        .insert(Transform::from_translation(position))
+        .insert(ParticleEmitter::burst(effect, count));
+}
+
+fn particle_from_events_system(
+    mut commands: Commands,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut died_events: EventReader<PlayerDied>,
+    mut impact_events: EventReader<LaserImpact>,
+) {
+    for event in killed_events.iter() {
+        spawn_burst(
+            &mut commands,
+            ParticleEffect::ENEMY_DEBRIS,
+            24,
+            event.position,
+        );
+    }
+    for event in died_events.iter() {
+        spawn_burst(
+            &mut commands,
+            ParticleEffect::PLAYER_DEBRIS,
+            60,
+            event.position,
+        );
+    }
+    for event in impact_events.iter() {
+        spawn_burst(
+            &mut commands,
+            ParticleEffect::LASER_SPARKS,
+            8,
+            event.position,
+        );
+    }
+}
+
+fn particle_emitter_system(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    budget: Res<ParticleBudget>,
+    clock: Res<GameClock>,
+    mut emitter_query: Query<(Entity, &mut ParticleEmitter, &Transform)>,
+    particle_query: Query<(), (With<Particle>, Without<Pooled>)>,
+) {
+    let mut rng = thread_rng(); // looks only, keep the GameRng sequence for gameplay
+    let mut free = budget.0.saturating_sub(particle_query.iter().count());
+
+    for (entity, mut emitter, transform) in emitter_query.iter_mut() {
+        let count = match emitter.mode {
+            EmitMode::Burst { count } => {
+                commands.entity(entity).despawn();
+                count
+            }
+            EmitMode::Continuous { rate } => {
+                emitter.carry += rate * clock.delta_seconds();
+                let count = emitter.carry.floor();
+                emitter.carry -= count;
+                count as u32
+            }
+        };
+
+        let count = (count as usize).min(free);
+        free -= count;
+
+        let effect = emitter.effect;
+        let origin = transform.translation.truncate() + emitter.offset;
+        for _ in 0..count {
+            let angle = effect.direction + rng.gen_range(-0.5..=0.5) * effect.spread;
+            let speed = rng.gen_range(effect.speed.0..=effect.speed.1);
+            let particle = Particle {
+                velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                age: 0.,
+                lifetime: rng.gen_range(effect.lifetime.0..=effect.lifetime.1),
+                gravity: effect.gravity,
+                drag: effect.drag,
+                color: effect.color,
+                size: effect.size,
+            };
+            let bundle = SpriteBundle {
+                sprite: Sprite {
+                    color: effect.color.0,
+                    custom_size: Some(Vec2::splat(effect.size.0)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(origin.extend(5.)),
+                ..Default::default()
+            };
+            pool.acquire(&mut commands, PoolKind::Particle, bundle)
+                .insert(particle);
+        }
+    }
+}
+
+fn particle_update_system(
+    mut commands: Commands,
+    mut pool: ResMut<EntityPool>,
+    clock: Res<GameClock>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite), Without<Pooled>>,
+) {
+    let delta = clock.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            pool.release(&mut commands, entity, PoolKind::Particle);
+            continue;
+        }
+
+        let gravity = particle.gravity;
+        let drag = (1. - particle.drag * delta).max(0.);
+        particle.velocity = (particle.velocity + gravity * delta) * drag;
+        transform.translation += (particle.velocity * delta).extend(0.);
+
+        // color and size over lifetime
+        let t = particle.age / particle.lifetime;
+        let (start, end) = particle.color;
+        let color = Vec4::from(start.as_rgba_f32()).lerp(Vec4::from(end.as_rgba_f32()), t);
+        sprite.color = Color::rgba(color.x, color.y, color.z, color.w);
+        let size = particle.size.0 + (particle.size.1 - particle.size.0) * t;
+        sprite.custom_size = Some(Vec2::splat(size));
+    }
+}
@@ -0,0 +1,104 @@
+// persistence and clock, files on desktop and localStorage on wasm
+
+const APP_DIR: &str = "undead-germ4ns";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path(key: &str) -> std::path::PathBuf {
+    use std::{env, path::PathBuf};
+
+    // XDG data dir, %APPDATA% on windows, working dir as last resort
+    let base = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    base.join(APP_DIR).join(format!("{}.ron", key))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(save_path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    let path = save_path(key);
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, value));
+
+    if let Err(err) = result {
+        bevy::log::warn!("could not save {}: {}", path.display(), err);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_millis() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("{}/{}", APP_DIR, key))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    let saved = local_storage().map(|storage| {
+        storage
+            .set_item(&format!("{}/{}", APP_DIR, key), value)
+            .is_ok()
+    });
+
+    if saved != Some(true) {
+        bevy::log::warn!("could not save {} to localStorage", key);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn unix_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// YYYY-MM-DD (UTC) for a unix timestamp in seconds
+pub fn format_date(unix_secs: u64) -> String {
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_date_known_days() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(86_399), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+        assert_eq!(format_date(1_735_689_600), "2025-01-01");
+    }
+}
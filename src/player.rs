@@ -1,122 +1,377 @@
 use bevy::{
-    math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide, time::FixedTimestep, transform,
+    ecs::system::EntityCommands, math::Vec3Swizzles, prelude::*, sprite::collide_aabb::collide,
+    time::FixedTimestep, transform,
 };
 
 // using strg . here in vsc to gain knowledge about imports
 use crate::{
-    components::{Enemy, FromPlayer, Laser, Movable, Player, SpriteSize, Velocity},
-    GameTextures, PlayerState, WinSize, BASE_SPEED, PLAYER_LASER_SIZE, PLAYER_RESPAWN_DELAY,
-    PLAYER_SIZE, SPRITE_SCALE, TIME_STEP,
+    character::{PlayerCharacter, PrimaryWeapon, SelectedCharacters, Special, SpecialState},
+    components::{
+        Collider, DespawnPolicy, Enemy, FromEnemy, FromPlayer, Laser, Movable, Owner, Player,
+        PlayerId, SpriteSize, Swept, Velocity,
+    },
+    controls::PlayerInputs,
+    events::{EnemyKilled, ExplosionToSpawn, PlayerDied, PlayerHit, ShotFired},
+    particle::{ParticleEffect, ParticleEmitter},
+    playing_criteria,
+    pool::{EntityPool, PoolKind, PoolMember, Pooled},
+    settings::Settings,
+    sound::{PlaySfx, Sfx},
+    weapon::WeaponState,
+    AppState, GameClock, GameConfig, GameTextures, PlayerState, PlayerStates, WinSize,
+    COOP_SPAWN_SPREAD, DASH_SPEED, DASH_TIME, ENGINE_TRAIL_RATE, GAME_OVER_DELAY,
+    PLAYER_LASER_HITBOX, PROJECTILE_DESPAWN_MARGIN, REVIVE_KILLS, SPREAD_ANGLE, SWEPT_SPEED_MIN,
+    TIME_STEP,
 };
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlayerState::default())
+        app.insert_resource(PlayerStates::default())
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(player_reset_system))
             .add_system_set(
                 SystemSet::new()
-                    .with_run_criteria(FixedTimestep::step(0.5))
+                    .with_run_criteria(FixedTimestep::step(0.5).chain(playing_criteria))
                     .with_system(player_spawn_system),
             )
-            .add_system(player_keyboard_event_system)
-            .add_system(player_fire_system);
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(player_input_system)
+                    .with_system(player_fire_system)
+                    .with_system(player_special_system)
+                    .with_system(player_hit_system)
+                    .with_system(player_revive_system)
+                    .with_system(game_over_check_system),
+            );
     }
 }
 
-// - add player
+// - add players, each one respawns on its own
 fn player_spawn_system(
     mut commands: Commands,
-    mut player_state: ResMut<PlayerState>,
-    time: Res<Time>,
+    mut player_states: ResMut<PlayerStates>,
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    images: Res<Assets<Image>>,
     game_textures: Res<GameTextures>,
+    selected: Res<SelectedCharacters>,
     win_size: Res<WinSize>,
 ) {
     //prep playerState
-    let now = time.seconds_since_startup();
-    let last_shot = player_state.last_shot;
+    let now = clock.elapsed();
+    let count = player_states.joined().count();
+
+    for (id, player_state) in player_states.0.iter_mut().enumerate() {
+        let last_shot = player_state.last_shot;
+        let character = match selected.character(id) {
+            Some(character) if player_state.joined => character,
+            _ => continue,
+        };
+        if player_state.on
+            || player_state.lives == 0
+            || (last_shot != -1. && now <= last_shot + config.player_respawn_delay)
+        {
+            continue;
+        }
 
-    if !player_state.on && (last_shot == -1. || now > last_shot + PLAYER_RESPAWN_DELAY) {
-        //add player
+        //add player, side by side in co-op
         let bottom = -win_size.h / 2.;
+        let x = (id as f32 - (count - 1) as f32 / 2.) * COOP_SPAWN_SPREAD;
+        let texture = character.sprite.texture(&game_textures);
+        let size = images
+            .get(&texture)
+            .map_or(Vec2::ZERO, |image| image.size());
         commands
             .spawn_bundle(SpriteBundle {
-                texture: game_textures.player.clone(),
+                texture,
                 transform: Transform {
-                    translation: Vec3::new(0., bottom + PLAYER_SIZE.1 / 2. * SPRITE_SCALE + 5., 1.),
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+                    translation: Vec3::new(x, bottom + size.y / 2. * config.sprite_scale + 5., 1.),
+                    scale: Vec3::new(config.sprite_scale, config.sprite_scale, 1.),
                     ..Default::default()
                 },
                 ..Default::default()
             })
             .insert(Player)
-            .insert(SpriteSize::from(PLAYER_SIZE))
-            .insert(Movable {
-                auto_despawn: false,
-            })
-            .insert(Velocity { x: 0., y: 0. });
+            .insert(PlayerId(id))
+            .insert(*character)
+            .insert(SpecialState::default())
+            .insert(Collider::player())
+            .insert(config.hitbox(character.key, character.hitbox))
+            .insert(Movable)
+            .insert(Velocity { x: 0., y: 0. })
+            .insert(ParticleEmitter::continuous(
+                ParticleEffect::ENGINE_TRAIL,
+                ENGINE_TRAIL_RATE,
+                Vec2::new(0., -size.y / 2. * config.sprite_scale),
+            ));
 
         player_state.spawned();
     }
 }
 
-fn player_movement_system(mut query: Query<(&Velocity, &mut Transform), With<Player>>) {
+fn player_movement_system(
+    config: Res<GameConfig>,
+    mut query: Query<(&Velocity, &mut Transform), With<Player>>,
+) {
     //this is a good place to keep player inside the windo w
 
     for (velocity, mut transform) in query.iter_mut() {
         let translation = &mut transform.translation;
-        translation.x += velocity.x * TIME_STEP * BASE_SPEED;
-        translation.y += velocity.y * TIME_STEP * BASE_SPEED;
+        translation.x += velocity.x * TIME_STEP * config.base_speed;
+        translation.y += velocity.y * TIME_STEP * config.base_speed;
     }
 }
 
 // --- jeremy says "that is a magic"
-fn player_keyboard_event_system(
-    kb: Res<Input<KeyCode>>,
-    mut query: Query<&mut Velocity, With<Player>>,
+fn player_input_system(
+    inputs: Res<PlayerInputs>,
+    clock: Res<GameClock>,
+    mut query: Query<(&PlayerId, &mut Velocity, &PlayerCharacter, &SpecialState), With<Player>>,
 ) {
-    if let Ok(mut velocity) = query.get_single_mut() {
-        velocity.x = if kb.pressed(KeyCode::Left) {
-            -1.
-        } else if kb.pressed(KeyCode::Right) {
-            1.
-        } else {
-            0.
-        }
-    };
+    for (id, mut velocity, character, special) in query.iter_mut() {
+        let direction = inputs.0[id.0].x;
+        let dashing = clock.elapsed() < special.dash_until;
+        let boost = if dashing { DASH_SPEED } else { 1. };
+        velocity.x = direction * character.speed * boost;
+    }
 }
 
-fn player_fire_system(
+pub fn player_fire_system(
     mut commands: Commands,
-    kb: Res<Input<KeyCode>>,
+    clock: Res<GameClock>,
+    inputs: Res<PlayerInputs>,
+    settings: Res<Settings>,
+    config: Res<GameConfig>,
     game_textures: Res<GameTextures>,
-    query: Query<&Transform, With<Player>>,
+    mut pool: ResMut<EntityPool>,
+    mut shot_fired_events: EventWriter<ShotFired>,
+    query: Query<
+        (
+            Entity,
+            &PlayerId,
+            &Transform,
+            &SpriteSize,
+            &PlayerCharacter,
+            Option<&WeaponState>,
+        ),
+        With<Player>,
+    >,
 ) {
-    if let Ok(player_tf) = query.get_single() {
-        if kb.pressed(KeyCode::Space) {
+    // fires every frame, a frozen frame would stack lasers
+    if clock.stopped() {
+        return;
+    }
+    let now = clock.elapsed();
+    for (player_entity, player_id, player_tf, player_size, character, weapon) in query.iter() {
+        if weapon.is_some_and(|weapon| weapon.charging(now)) {
+            continue;
+        }
+        // auto fire keeps shooting without holding fire
+        if inputs.0[player_id.0].fire || settings.auto_fire {
             let (x, y) = (player_tf.translation.x, player_tf.translation.y);
-            //let x_offset = PLAYER_SIZE.0 / 2. * SPRITE_SCALE - 15.;
-            let y_offset = PLAYER_SIZE.1 / 2. * SPRITE_SCALE;
-
-            let mut spawn_laser = |x_offset: f32| {
-                commands
-                    .spawn_bundle(SpriteBundle {
-                        texture: game_textures.player_laser.clone(),
-                        transform: Transform {
-                            translation: Vec3::new(x + x_offset, y + y_offset - 2., 2.),
-                            scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    })
-                    .insert(Laser)
-                    .insert(FromPlayer)
-                    .insert(SpriteSize::from(PLAYER_LASER_SIZE))
-                    .insert(Movable { auto_despawn: true })
-                    .insert(Velocity { x: 0., y: 1. });
+            //let x_offset = player_size.0.x / 2. * config.sprite_scale - 15.;
+            let y_offset = player_size.0.y / 2. * config.sprite_scale;
+
+            let mut spawn_laser = |x_offset: f32, x_velocity: f32| {
+                let translation = Vec3::new(x + x_offset, y + y_offset - 2., 2.);
+                spawn_player_laser(
+                    &mut commands,
+                    &mut pool,
+                    &config,
+                    &game_textures,
+                    player_entity,
+                    *player_id,
+                    translation,
+                    x_velocity,
+                );
             };
 
-            spawn_laser(PLAYER_SIZE.0 / 2. * SPRITE_SCALE - 47.);
-            spawn_laser(PLAYER_SIZE.0 / 2. * SPRITE_SCALE - 37.);
+            match character.weapon {
+                PrimaryWeapon::Twin => {
+                    spawn_laser(player_size.0.x / 2. * config.sprite_scale - 47., 0.);
+                    spawn_laser(player_size.0.x / 2. * config.sprite_scale - 37., 0.);
+                }
+                PrimaryWeapon::Spread => {
+                    for x_velocity in [-SPREAD_ANGLE, 0., SPREAD_ANGLE] {
+                        spawn_laser(0., x_velocity);
+                    }
+                }
+            }
+
+            shot_fired_events.send(ShotFired {
+                by: player_entity,
+                position: player_tf.translation,
+                from_player: true,
+            });
+        }
+    }
+}
+
+/// one player laser flying up (x_velocity sideways), drones shoot these too
+pub fn spawn_player_laser<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    pool: &mut EntityPool,
+    config: &GameConfig,
+    game_textures: &GameTextures,
+    owner: Entity,
+    player: PlayerId,
+    translation: Vec3,
+    x_velocity: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    let velocity = Velocity {
+        x: x_velocity,
+        y: 1.,
+    };
+    let swept = velocity.length() * config.base_speed > SWEPT_SPEED_MIN;
+
+    let bundle = SpriteBundle {
+        texture: game_textures.player_laser.clone(),
+        transform: Transform {
+            translation,
+            // point along the flight direction
+            rotation: Quat::from_rotation_z(-x_velocity.atan2(1.)),
+            scale: Vec3::new(config.sprite_scale, config.sprite_scale, 1.),
+        },
+        ..Default::default()
+    };
+    let mut laser = pool.acquire(commands, PoolKind::PlayerLaser, bundle);
+    laser
+        .insert(Laser)
+        .insert(FromPlayer)
+        .insert(Owner(owner))
+        .insert(player)
+        .insert(Collider::player_laser())
+        .insert(config.hitbox("player_laser", PLAYER_LASER_HITBOX))
+        .insert(Movable)
+        .insert(DespawnPolicy::OffScreen {
+            margin: PROJECTILE_DESPAWN_MARGIN,
+        })
+        .insert(velocity);
+    if swept {
+        laser.insert(Swept::from(translation));
+    }
+    laser
+}
+
+// shift fires the special of the ship, each has its own cooldown
+fn player_special_system(
+    mut commands: Commands,
+    inputs: Res<PlayerInputs>,
+    clock: Res<GameClock>,
+    mut pool: ResMut<EntityPool>,
+    mut sfx_events: EventWriter<PlaySfx>,
+    mut player_query: Query<(&PlayerId, &PlayerCharacter, &mut SpecialState), With<Player>>,
+    laser_query: Query<
+        (Entity, Option<&PoolMember>),
+        (With<Laser>, With<FromEnemy>, Without<Pooled>),
+    >,
+) {
+    let now = clock.elapsed();
+    let mut bombed = false;
+    for (id, character, mut special) in player_query.iter_mut() {
+        if !inputs.0[id.0].special_pressed || now < special.ready_at {
+            continue;
+        }
+        special.ready_at = now + character.special.cooldown();
+        match character.special {
+            Special::Bomb => {
+                // two bombs in the same frame clear the lasers once
+                if !bombed {
+                    for (entity, member) in laser_query.iter() {
+                        pool.despawn(&mut commands, entity, member);
+                    }
+                    bombed = true;
+                }
+                sfx_events.send(PlaySfx(Sfx::Explosion));
+            }
+            Special::Dash => special.dash_until = now + DASH_TIME,
         }
     }
 }
+
+// every hit is deadly for now
+fn player_hit_system(
+    mut commands: Commands,
+    mut player_states: ResMut<PlayerStates>,
+    clock: Res<GameClock>,
+    mut player_hit_events: EventReader<PlayerHit>,
+    mut player_died_events: EventWriter<PlayerDied>,
+    mut explosion_events: EventWriter<ExplosionToSpawn>,
+    query: Query<&PlayerId, With<Player>>,
+) {
+    for hit in player_hit_events.iter() {
+        let player_state = match query.get(hit.player) {
+            Ok(id) => &mut player_states.0[id.0],
+            Err(_) => continue,
+        };
+        // several lasers can hit in the same frame, die only once
+        if !player_state.on {
+            continue;
+        }
+        commands.entity(hit.player).despawn_recursive();
+        player_state.shot(clock.elapsed());
+        player_state.lives = player_state.lives.saturating_sub(1);
+
+        explosion_events.send(ExplosionToSpawn(hit.position));
+        player_died_events.send(PlayerDied {
+            player: hit.player,
+            position: hit.position,
+        });
+    }
+}
+
+// co-op: a player out of lives comes back with one once the team got enough kills
+fn player_revive_system(
+    mut player_states: ResMut<PlayerStates>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    let kills = killed_events.iter().count() as u32;
+    if kills == 0 {
+        return;
+    }
+    for player_state in player_states.0.iter_mut().filter(|state| state.out()) {
+        player_state.revive_kills += kills;
+        if player_state.revive_kills >= REVIVE_KILLS {
+            player_state.revive_kills = 0;
+            player_state.lives = 1;
+        }
+    }
+}
+
+// everyone out of lives, give the last explosion a moment before ending the game
+fn game_over_check_system(
+    player_states: Res<PlayerStates>,
+    clock: Res<GameClock>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let now = clock.elapsed();
+    let last_death = player_states
+        .joined()
+        .map(|player_state| player_state.last_shot)
+        .fold(-1., f64::max);
+    if player_states.joined().all(PlayerState::out) && now > last_death + GAME_OVER_DELAY {
+        let _ = state.set(AppState::GameOver);
+    }
+}
+
+fn player_reset_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    selected: Res<SelectedCharacters>,
+    mut player_states: ResMut<PlayerStates>,
+    query: Query<Entity, With<Player>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (id, player_state) in player_states.0.iter_mut().enumerate() {
+        *player_state = match selected.character(id) {
+            Some(character) => PlayerState {
+                joined: true,
+                lives: settings.difficulty.lives(character.lives),
+                ..Default::default()
+            },
+            None => PlayerState::default(),
+        };
+    }
+}
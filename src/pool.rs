@@ -0,0 +1,171 @@
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    components::{Damage, Piercing, Swept},
+    graze::{Grazed, Grazing},
+    weapon::Homing,
+};
+
+// components an entity can pick up during one life, stripped before it is reused
+type Transient = (Swept, Grazed, Grazing, Piercing, Homing, Damage);
+
+pub struct PoolPlugin;
+
+impl Plugin for PoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EntityPool::default());
+    }
+}
+
+// -- Component - entity is parked in the pool, gameplay systems skip it
+#[derive(Component)]
+pub struct Pooled;
+
+// -- Component - pool the entity returns to instead of being despawned
+#[derive(Component, Clone, Copy)]
+pub struct PoolMember(pub PoolKind);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PoolKind {
+    PlayerLaser,
+    EnemyLaser,
+    Explosion,
+    Particle,
+}
+
+// -Ressource - parked entities per kind
+#[derive(Default)]
+pub struct EntityPool {
+    free: HashMap<PoolKind, Vec<Entity>>,
+    // guards against releasing the same entity twice in one frame
+    parked: HashSet<Entity>,
+}
+
+impl EntityPool {
+    /// reuses a parked entity of `kind` (clearing leftovers, overwriting its bundle) or spawns a new one
+    pub fn acquire<'w, 's, 'a>(
+        &mut self,
+        commands: &'a mut Commands<'w, 's>,
+        kind: PoolKind,
+        bundle: impl Bundle,
+    ) -> EntityCommands<'w, 's, 'a> {
+        match self.free.get_mut(&kind).and_then(|free| free.pop()) {
+            Some(entity) => {
+                self.parked.remove(&entity);
+                let mut entity_commands = commands.entity(entity);
+                entity_commands
+                    .remove_bundle::<Transient>()
+                    .insert_bundle(bundle)
+                    .remove::<Pooled>();
+                entity_commands
+            }
+            None => {
+                let mut entity_commands = commands.spawn_bundle(bundle);
+                entity_commands.insert(PoolMember(kind));
+                entity_commands
+            }
+        }
+    }
+
+    /// hides the entity and parks it for the next acquire
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity, kind: PoolKind) {
+        if !self.parked.insert(entity) {
+            return;
+        }
+        commands
+            .entity(entity)
+            .insert(Visibility { is_visible: false })
+            .insert(Pooled);
+        self.free.entry(kind).or_default().push(entity);
+    }
+
+    /// releases pool members, despawns everything else
+    pub fn despawn(
+        &mut self,
+        commands: &mut Commands,
+        entity: Entity,
+        member: Option<&PoolMember>,
+    ) {
+        match member {
+            Some(member) => self.release(commands, entity, member.0),
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    pub fn free_count(&self, kind: PoolKind) -> usize {
+        self.free.get(&kind).map_or(0, Vec::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+
+    // runs one frame worth of commands against the world
+    fn frame<T>(world: &mut World, f: impl FnOnce(&mut Commands) -> T) -> T {
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        let out = f(&mut commands);
+        queue.apply(world);
+        out
+    }
+
+    #[test]
+    fn reacquire_reuses_clean_entity() {
+        let mut world = World::new();
+        let mut pool = EntityPool::default();
+
+        let first = frame(&mut world, |commands| {
+            pool.acquire(commands, PoolKind::EnemyLaser, TransformBundle::default())
+                .insert(Grazed)
+                .insert(Swept::from(Vec3::ZERO))
+                .insert(Piercing::default())
+                .insert(Homing)
+                .insert(Damage(3))
+                .id()
+        });
+        frame(&mut world, |commands| {
+            pool.release(commands, first, PoolKind::EnemyLaser)
+        });
+        assert!(world.entity(first).contains::<Pooled>());
+        assert_eq!(pool.free_count(PoolKind::EnemyLaser), 1);
+
+        let second = frame(&mut world, |commands| {
+            pool.acquire(commands, PoolKind::EnemyLaser, TransformBundle::default())
+                .id()
+        });
+        assert_eq!(first, second);
+        assert_eq!(pool.free_count(PoolKind::EnemyLaser), 0);
+
+        let entity = world.entity(second);
+        assert!(entity.contains::<PoolMember>());
+        assert!(!entity.contains::<Pooled>());
+        assert!(!entity.contains::<Grazed>());
+        assert!(!entity.contains::<Swept>());
+        assert!(!entity.contains::<Piercing>());
+        assert!(!entity.contains::<Homing>());
+        assert!(!entity.contains::<Damage>());
+    }
+
+    #[test]
+    fn double_release_parks_once() {
+        let mut world = World::new();
+        let mut pool = EntityPool::default();
+
+        let entity = frame(&mut world, |commands| {
+            pool.acquire(commands, PoolKind::Particle, TransformBundle::default())
+                .id()
+        });
+        frame(&mut world, |commands| {
+            pool.release(commands, entity, PoolKind::Particle);
+            pool.release(commands, entity, PoolKind::Particle);
+        });
+        assert_eq!(pool.free_count(PoolKind::Particle), 1);
+    }
+}
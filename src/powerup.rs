@@ -0,0 +1,129 @@
+use bevy::{prelude::*, utils::HashSet};
+use rand::Rng;
+
+use crate::{
+    collision::{collision_detection_system, CollisionEvent},
+    components::{Collider, DespawnPolicy, Movable, Player, SpriteSize, Velocity},
+    events::{EnemyKilled, PowerUpCollected},
+    sound::{PlaySfx, Sfx},
+    AppState, GameClock, GameRng, POWER_UP_DROP_CHANCE, POWER_UP_SIZE, POWER_UP_SPEED,
+    PROJECTILE_DESPAWN_MARGIN,
+};
+
+// -- pickups some enemies leave behind, they fall down until a player catches them
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Playing).with_system(power_up_reset_system),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Playing)
+                .with_system(power_up_drop_system)
+                .with_system(power_up_pickup_system.after(collision_detection_system))
+                .with_system(power_up_spin_system),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUpKind {
+    Option, // one more drone
+}
+
+impl PowerUpKind {
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::Option => Color::rgb(1., 0.85, 0.3),
+        }
+    }
+}
+
+// -- Component
+#[derive(Component)]
+pub struct PowerUp(pub PowerUpKind);
+
+fn power_up_drop_system(
+    mut commands: Commands,
+    mut game_rng: ResMut<GameRng>,
+    mut killed_events: EventReader<EnemyKilled>,
+) {
+    for event in killed_events.iter() {
+        if !game_rng.rng.gen_bool(POWER_UP_DROP_CHANCE) {
+            continue;
+        }
+        let kind = PowerUpKind::Option;
+        let size = Vec2::splat(POWER_UP_SIZE);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(event.position.truncate().extend(3.)),
+                ..Default::default()
+            })
+            .insert(PowerUp(kind))
+            .insert(Collider::power_up())
+            .insert(SpriteSize(size)) // no image to take it from
+            .insert(Movable)
+            .insert(Velocity {
+                x: 0.,
+                y: -POWER_UP_SPEED,
+            })
+            .insert(DespawnPolicy::OffScreen {
+                margin: PROJECTILE_DESPAWN_MARGIN,
+            });
+    }
+}
+
+fn power_up_pickup_system(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut collected_events: EventWriter<PowerUpCollected>,
+    mut sfx_events: EventWriter<PlaySfx>,
+    power_up_query: Query<(&PowerUp, &Transform)>,
+    player_query: Query<(), With<Player>>,
+) {
+    // both players touching it in the same frame, the first one gets it
+    let mut collected: HashSet<Entity> = HashSet::new();
+
+    for event in collision_events.iter() {
+        let (power_up_entity, player_entity) =
+            match event.ordered(|entity| power_up_query.contains(entity)) {
+                Some(pair) => pair,
+                None => continue,
+            };
+        if collected.contains(&power_up_entity) || !player_query.contains(player_entity) {
+            continue;
+        }
+
+        if let Ok((power_up, transform)) = power_up_query.get(power_up_entity) {
+            commands.entity(power_up_entity).despawn();
+            collected.insert(power_up_entity);
+
+            sfx_events.send(PlaySfx(Sfx::Pickup));
+            collected_events.send(PowerUpCollected {
+                player: player_entity,
+                kind: power_up.0,
+                position: transform.translation,
+            });
+        }
+    }
+}
+
+// turns so it stands out from the lasers
+fn power_up_spin_system(clock: Res<GameClock>, mut query: Query<&mut Transform, With<PowerUp>>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_z(3. * clock.delta_seconds());
+    }
+}
+
+fn power_up_reset_system(mut commands: Commands, query: Query<Entity, With<PowerUp>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,385 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    components::EnemyArchetype,
+    events::{EnemyEscaped, EnemyKilled, GroupCleared},
+    settings::Settings,
+    AppState, GameClock, GameConfig, GameFonts, PlayerStates, COMBO_DECAY, COMBO_MAX, COMBO_STEP,
+    COMBO_WINDOW, GROUP_CLEAR_BONUS, MAX_PLAYERS, REVIVE_KILLS, SCORE_POPUP_TIME,
+};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Scores::default())
+            .insert_resource(GroupTally::default())
+            .add_startup_system_to_stage(StartupStage::PostStartup, score_hud_setup_system)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(score_reset_system))
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(score_kill_system)
+                    .with_system(score_combo_decay_system)
+                    .with_system(score_popup_system),
+            )
+            .add_system(score_hud_system);
+    }
+}
+
+// region: --- Resources
+pub struct Score {
+    pub points: u64,
+    pub multiplier: f32,
+    last_kill: f64, // -1 if no kill yet
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            points: 0,
+            multiplier: 1.,
+            last_kill: -1.,
+        }
+    }
+}
+
+impl Score {
+    /// adds base points times the current multiplier, returns what was added
+    pub fn add(&mut self, base: u64) -> u64 {
+        let points = (base as f32 * self.multiplier).round() as u64;
+        self.points += points;
+        points
+    }
+
+    pub fn kill(&mut self, time: f64) {
+        if self.last_kill >= 0. && time - self.last_kill <= COMBO_WINDOW {
+            self.multiplier = (self.multiplier + COMBO_STEP).min(COMBO_MAX);
+        }
+        self.last_kill = time;
+    }
+
+    /// bleeds the multiplier back toward 1 once the combo window is over
+    pub fn decay(&mut self, time: f64, delta: f32) {
+        if self.last_kill >= 0. && time - self.last_kill > COMBO_WINDOW && self.multiplier > 1. {
+            self.multiplier = (self.multiplier - COMBO_DECAY * delta).max(1.);
+        }
+    }
+}
+
+// -Ressource - one Score per PlayerId, co-op ranks by the total
+#[derive(Default)]
+pub struct Scores(pub [Score; MAX_PLAYERS]);
+
+impl Scores {
+    pub fn total(&self) -> u64 {
+        self.0.iter().map(|score| score.points).sum()
+    }
+
+    pub fn best_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|score| score.multiplier)
+            .fold(1., f32::max)
+    }
+}
+
+// kills and escapes per formation group
+#[derive(Default)]
+struct GroupTally(HashMap<u32, (u32, u32)>);
+
+impl GroupTally {
+    /// counts a kill, true when it was the last member and nobody escaped
+    fn kill(&mut self, group: u32, members: u32) -> bool {
+        let (killed, escaped) = self.0.entry(group).or_default();
+        *killed += 1;
+        *killed == members && *escaped == 0
+    }
+
+    fn escape(&mut self, group: u32) {
+        self.0.entry(group).or_default().1 += 1;
+    }
+
+    // forget groups where every member is accounted for
+    fn forget_done(&mut self, members: u32) {
+        self.0
+            .retain(|_, (killed, escaped)| *killed + *escaped < members);
+    }
+}
+// endregion: --- Resources
+
+// region: --- Components
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct ScorePopup(Timer);
+// endregion: --- Components
+
+pub fn base_points(archetype: EnemyArchetype) -> u64 {
+    match archetype {
+        EnemyArchetype::Grunt => 100,
+    }
+}
+
+fn score_kill_system(
+    mut commands: Commands,
+    mut scores: ResMut<Scores>,
+    mut tally: ResMut<GroupTally>,
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    fonts: Res<GameFonts>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut escaped_events: EventReader<EnemyEscaped>,
+    mut cleared_events: EventWriter<GroupCleared>,
+) {
+    let now = clock.elapsed();
+
+    for event in killed_events.iter() {
+        // the group bonus goes to whoever got the last one
+        let score = &mut scores.0[event.player.0];
+        score.kill(now);
+        let points = score.add(base_points(event.archetype));
+        spawn_popup(
+            &mut commands,
+            &fonts,
+            event.position,
+            format!("+{}", points),
+        );
+
+        // whole group shot down before anyone escaped
+        if tally.kill(event.group, config.formation_members_max) {
+            let bonus = score.add(GROUP_CLEAR_BONUS);
+            let position = event.position + Vec3::new(0., 30., 0.);
+            spawn_popup(&mut commands, &fonts, position, format!("GROUP +{}", bonus));
+            cleared_events.send(GroupCleared {
+                group: event.group,
+                position: event.position,
+                bonus,
+            });
+        }
+    }
+
+    for event in escaped_events.iter() {
+        tally.escape(event.group);
+    }
+    tally.forget_done(config.formation_members_max);
+}
+
+fn score_combo_decay_system(mut scores: ResMut<Scores>, clock: Res<GameClock>) {
+    let now = clock.elapsed();
+    for score in scores.0.iter_mut() {
+        score.decay(now, clock.delta_seconds());
+    }
+}
+
+fn spawn_popup(commands: &mut Commands, fonts: &GameFonts, position: Vec3, value: String) {
+    let style = TextStyle {
+        font: fonts.main.clone(),
+        font_size: 20.,
+        color: Color::rgb(1., 0.85, 0.3),
+    };
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(value, style).with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(position.truncate().extend(50.)),
+            ..Default::default()
+        })
+        .insert(ScorePopup(Timer::from_seconds(SCORE_POPUP_TIME, false)));
+}
+
+// rise and fade out
+fn score_popup_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in query.iter_mut() {
+        popup.0.tick(clock.delta());
+        transform.translation.y += 40. * clock.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(popup.0.percent_left());
+        }
+        if popup.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn score_hud_setup_system(mut commands: Commands, fonts: Res<GameFonts>) {
+    let style = TextStyle {
+        font: fonts.main.clone(),
+        font_size: 28.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(TextBundle::from_section("", style).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(12.),
+                top: Val::Px(8.),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .insert(ScoreText);
+}
+
+// one line per player, alone it reads like it always did
+fn score_hud_system(
+    scores: Res<Scores>,
+    player_states: Res<PlayerStates>,
+    settings: Res<Settings>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    if !scores.is_changed() && !player_states.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let lang = settings.language;
+    let coop = player_states.joined().count() > 1;
+    let lines: Vec<String> = player_states
+        .0
+        .iter()
+        .zip(scores.0.iter())
+        .enumerate()
+        .filter(|(_, (state, _))| state.joined)
+        .map(|(id, (state, score))| {
+            let status = if state.out() {
+                format!(
+                    "{} {}/{}",
+                    lang.tr("REVIVE"),
+                    state.revive_kills,
+                    REVIVE_KILLS
+                )
+            } else {
+                format!("{} {}", lang.tr("LIVES"), state.lives)
+            };
+            let line = format!("{:08}  x{:.1}   {}", score.points, score.multiplier, status);
+            if coop {
+                format!("P{} {}", id + 1, line)
+            } else {
+                line
+            }
+        })
+        .collect();
+    for mut text in query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn score_reset_system(mut commands: Commands, query: Query<Entity, With<ScorePopup>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.insert_resource(Scores::default());
+    commands.insert_resource(GroupTally::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMBERS: u32 = 4;
+
+    #[test]
+    fn combo_builds_inside_window() {
+        let mut score = Score::default();
+        score.kill(0.);
+        assert_eq!(score.multiplier, 1.);
+        score.kill(COMBO_WINDOW);
+        score.kill(COMBO_WINDOW * 2.);
+        assert!((score.multiplier - (1. + 2. * COMBO_STEP)).abs() < 1e-5);
+
+        // too slow, the combo does not grow
+        score.kill(COMBO_WINDOW * 4.);
+        assert!((score.multiplier - (1. + 2. * COMBO_STEP)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn combo_caps_at_max() {
+        let mut score = Score::default();
+        for i in 0..1000 {
+            score.kill(i as f64 * 0.1);
+        }
+        assert_eq!(score.multiplier, COMBO_MAX);
+    }
+
+    #[test]
+    fn multiplier_scales_points() {
+        let mut score = Score {
+            multiplier: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(score.add(100), 150);
+        assert_eq!(score.points, 150);
+    }
+
+    #[test]
+    fn combo_decays_after_window() {
+        let mut score = Score::default();
+        score.kill(0.);
+        score.kill(1.);
+        let built = score.multiplier;
+        assert!(built > 1.);
+
+        // still inside the window
+        score.decay(1. + COMBO_WINDOW, 0.5);
+        assert_eq!(score.multiplier, built);
+
+        score.decay(1. + COMBO_WINDOW + 0.1, 0.05);
+        assert!((score.multiplier - (built - COMBO_DECAY * 0.05)).abs() < 1e-5);
+
+        // never drops below 1
+        score.decay(100., 10.);
+        assert_eq!(score.multiplier, 1.);
+    }
+
+    #[test]
+    fn no_decay_before_first_kill() {
+        let mut score = Score {
+            multiplier: 2.,
+            ..Default::default()
+        };
+        score.decay(100., 1.);
+        assert_eq!(score.multiplier, 2.);
+    }
+
+    #[test]
+    fn group_bonus_on_full_clear() {
+        let mut tally = GroupTally::default();
+        let cleared: Vec<bool> = (0..MEMBERS).map(|_| tally.kill(7, MEMBERS)).collect();
+        assert_eq!(cleared, [false, false, false, true]);
+
+        tally.forget_done(MEMBERS);
+        assert!(tally.0.is_empty());
+    }
+
+    #[test]
+    fn no_group_bonus_after_escape() {
+        let mut tally = GroupTally::default();
+        tally.escape(3);
+        for _ in 0..MEMBERS - 1 {
+            assert!(!tally.kill(3, MEMBERS));
+        }
+        tally.forget_done(MEMBERS);
+        assert!(tally.0.is_empty());
+    }
+
+    #[test]
+    fn groups_tally_apart() {
+        let mut tally = GroupTally::default();
+        for _ in 0..MEMBERS - 1 {
+            tally.kill(1, MEMBERS);
+            tally.kill(2, MEMBERS);
+        }
+        tally.escape(2);
+        assert!(tally.kill(1, MEMBERS));
+
+        tally.forget_done(MEMBERS);
+        assert_eq!(tally.0.len(), 0);
+
+        // an unfinished group is kept
+        tally.kill(5, MEMBERS);
+        tally.forget_done(MEMBERS);
+        assert_eq!(tally.0.get(&5), Some(&(1, 0)));
+    }
+}
@@ -0,0 +1,508 @@
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    window::{PresentMode, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controls::controls_system,
+    drone::DroneMode,
+    juice::{ReducedMotion, ShakeIntensity},
+    platform,
+    skin::Skins,
+    sound::AudioVolume,
+    ui::{label, screen_root},
+    AppState, GameClock, GameFonts, DEFAULT_SKIN,
+};
+
+const SETTINGS_KEY: &str = "settings";
+const VOLUME_STEP: f32 = 0.1;
+const SHAKE_STEP: f32 = 0.25;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .insert_resource(SettingsMenu::default())
+            .add_system(settings_apply_system)
+            // ahead of the player inputs, an open menu takes the keys for itself
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                settings_menu_input_system
+                    .after(InputSystem)
+                    .before(controls_system),
+            )
+            .add_system(settings_menu_ui_system)
+            .add_system(settings_menu_text_system.after(settings_menu_ui_system));
+    }
+}
+
+// region: --- Resources
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// scales how often enemies fire
+    pub fn enemy_fire_scale(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.6,
+        }
+    }
+
+    /// starting lives of a ship that has `lives` on normal
+    pub fn lives(&self, lives: u32) -> u32 {
+        match self {
+            Difficulty::Easy => lives + 2,
+            Difficulty::Normal => lives,
+            Difficulty::Hard => lives.saturating_sub(1).max(1),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Language {
+    English,
+    Deutsch,
+}
+
+impl Language {
+    const ALL: [Language; 2] = [Language::English, Language::Deutsch];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Language::English => "ENGLISH",
+            Language::Deutsch => "DEUTSCH",
+        }
+    }
+
+    /// ui text in this language, the english text is the key. unknown keys stay english
+    pub fn tr(&self, text: &'static str) -> &'static str {
+        match self {
+            Language::English => text,
+            Language::Deutsch => match text {
+                "SETTINGS" => "EINSTELLUNGEN",
+                "FULLSCREEN" => "VOLLBILD",
+                "VSYNC" => "VSYNC",
+                "MASTER VOLUME" => "GESAMTLAUTSTAERKE",
+                "MUSIC VOLUME" => "MUSIK",
+                "SFX VOLUME" => "EFFEKTE",
+                "SCREEN SHAKE" => "BILDSCHUETTELN",
+                "REDUCED MOTION" => "WENIGER BEWEGUNG",
+                "AUTO FIRE" => "DAUERFEUER",
+                "DRONES" => "DROHNEN",
+                "TRAIL" => "SPUR",
+                "ORBIT" => "KREIS",
+                "DIFFICULTY" => "SCHWIERIGKEIT",
+                "LANGUAGE" => "SPRACHE",
+                "SKIN" => "AUSSEHEN",
+                "BACK" => "ZURUECK",
+                "ON" => "AN",
+                "OFF" => "AUS",
+                "EASY" => "LEICHT",
+                "NORMAL" => "NORMAL",
+                "HARD" => "SCHWER",
+                "UP/DOWN select   LEFT/RIGHT change   ESC back" => {
+                    "HOCH/RUNTER waehlen   LINKS/RECHTS aendern   ESC zurueck"
+                }
+                "LIVES" => "LEBEN",
+                "SELECT YOUR SHIP" => "WAEHLE DEIN SCHIFF",
+                "SPEED" => "TEMPO",
+                "WEAPON" => "WAFFE",
+                "SPECIAL" => "SPEZIAL",
+                "TWIN" => "DOPPEL",
+                "SPREAD" => "STREU",
+                "BOMB" => "BOMBE",
+                "DASH" => "SPRINT",
+                "LEFT/RIGHT choose   ENTER start   SHIFT special   CTRL missiles" => {
+                    "LINKS/RECHTS waehlen   ENTER starten   SHIFT Spezial   STRG Raketen"
+                }
+                "P2 press F or pad A to join" => "P2 F oder Pad A zum Mitspielen",
+                "P2 G to leave" => "P2 G zum Aussteigen",
+                "REVIVE" => "WIEDERBELEBEN",
+                "GAME OVER" => "SPIEL VORBEI",
+                "NEW HIGH SCORE - ENTER YOUR NAME" => "NEUER HIGHSCORE - NAME EINGEBEN",
+                "UP/DOWN letter   LEFT/RIGHT move   ENTER confirm" => {
+                    "HOCH/RUNTER Buchstabe   LINKS/RECHTS bewegen   ENTER bestaetigen"
+                }
+                "press ENTER" => "ENTER druecken",
+                "HIGH SCORES" => "BESTENLISTE",
+                "no entries yet" => "noch keine Eintraege",
+                "LEFT/RIGHT mode   ENTER play again" => {
+                    "LINKS/RECHTS Modus   ENTER nochmal spielen"
+                }
+                _ => text,
+            },
+        }
+    }
+}
+
+// -Ressource - player options, saved on every change. fullscreen lives in WindowSettings
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)] // options added later fall back to their default
+pub struct Settings {
+    pub vsync: bool,
+    pub master_volume: f32, // 0..1
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub shake: f32, // 0..1 of the full camera shake
+    pub reduced_motion: bool,
+    pub auto_fire: bool,
+    pub drone_mode: DroneMode,
+    pub difficulty: Difficulty,
+    pub language: Language,
+    pub skin: String, // SkinPack name
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let volume = AudioVolume::default();
+        Self {
+            vsync: true,
+            master_volume: volume.master,
+            music_volume: volume.music,
+            sfx_volume: volume.sfx,
+            shake: 1.,
+            reduced_motion: false,
+            auto_fire: false,
+            drone_mode: DroneMode::Trail,
+            difficulty: Difficulty::Normal,
+            language: Language::English,
+            skin: DEFAULT_SKIN.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        platform::load(SETTINGS_KEY)
+            .and_then(|saved| ron::from_str(&saved).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        match ron::to_string(self) {
+            Ok(saved) => platform::save(SETTINGS_KEY, &saved),
+            Err(err) => warn!("could not serialize settings: {}", err),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SettingsItem {
+    Fullscreen,
+    Vsync,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Shake,
+    ReducedMotion,
+    AutoFire,
+    Drones,
+    Difficulty,
+    Language,
+    Skin,
+    Back,
+}
+
+impl SettingsItem {
+    const ALL: [SettingsItem; 13] = [
+        SettingsItem::Fullscreen,
+        SettingsItem::Vsync,
+        SettingsItem::MasterVolume,
+        SettingsItem::MusicVolume,
+        SettingsItem::SfxVolume,
+        SettingsItem::Shake,
+        SettingsItem::ReducedMotion,
+        SettingsItem::AutoFire,
+        SettingsItem::Drones,
+        SettingsItem::Difficulty,
+        SettingsItem::Language,
+        SettingsItem::Skin,
+        SettingsItem::Back,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SettingsItem::Fullscreen => "FULLSCREEN",
+            SettingsItem::Vsync => "VSYNC",
+            SettingsItem::MasterVolume => "MASTER VOLUME",
+            SettingsItem::MusicVolume => "MUSIC VOLUME",
+            SettingsItem::SfxVolume => "SFX VOLUME",
+            SettingsItem::Shake => "SCREEN SHAKE",
+            SettingsItem::ReducedMotion => "REDUCED MOTION",
+            SettingsItem::AutoFire => "AUTO FIRE",
+            SettingsItem::Drones => "DRONES",
+            SettingsItem::Difficulty => "DIFFICULTY",
+            SettingsItem::Language => "LANGUAGE",
+            SettingsItem::Skin => "SKIN",
+            SettingsItem::Back => "BACK",
+        }
+    }
+}
+
+// -Ressource - overlay over the game and the menu screens. the game clock stands
+// still while it is open, everything underneath keeps running without input
+#[derive(Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+    selected: usize,
+}
+// endregion: --- Resources
+
+// region: --- Components
+// built in this language, rebuilt when it changes
+#[derive(Component)]
+struct SettingsUi(Language);
+
+#[derive(Component)]
+struct SettingsRow(usize); // index into SettingsItem::ALL
+
+// endregion: --- Components
+
+// audio, shake and vsync follow the settings right away
+fn settings_apply_system(
+    settings: Res<Settings>,
+    mut volume: ResMut<AudioVolume>,
+    mut reduced_motion: ResMut<ReducedMotion>,
+    mut shake: ResMut<ShakeIntensity>,
+    mut windows: ResMut<Windows>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    *volume = AudioVolume {
+        master: settings.master_volume,
+        music: settings.music_volume,
+        sfx: settings.sfx_volume,
+    };
+    reduced_motion.0 = settings.reduced_motion;
+    shake.0 = settings.shake;
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_present_mode(if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        });
+    }
+}
+
+// escape opens the menu over the game and the menu screens
+pub fn settings_menu_input_system(
+    mut kb: ResMut<Input<KeyCode>>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    state: Res<State<AppState>>,
+    mut clock: ResMut<GameClock>,
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<Settings>,
+    skins: Res<Skins>,
+    mut windows: ResMut<Windows>,
+) {
+    if !menu.open {
+        let over = matches!(
+            state.current(),
+            AppState::Playing
+                | AppState::GameOver
+                | AppState::Leaderboard
+                | AppState::CharacterSelect
+        );
+        if over && kb.clear_just_pressed(KeyCode::Escape) {
+            menu.open = true;
+            clock.set_paused(true);
+        }
+        return;
+    }
+    settings_menu_keys(&kb, &mut menu, &mut settings, &skins, &mut windows);
+    clock.set_paused(menu.open);
+    // nothing underneath sees the presses
+    kb.clear();
+    buttons.clear();
+}
+
+fn settings_menu_keys(
+    kb: &Input<KeyCode>,
+    menu: &mut SettingsMenu,
+    settings: &mut Settings,
+    skins: &Skins,
+    windows: &mut Windows,
+) {
+    let count = SettingsItem::ALL.len();
+    if kb.just_pressed(KeyCode::Down) {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    if kb.just_pressed(KeyCode::Up) {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+
+    let item = SettingsItem::ALL[menu.selected];
+    if kb.just_pressed(KeyCode::Escape)
+        || (item == SettingsItem::Back && kb.just_pressed(KeyCode::Return))
+    {
+        menu.open = false;
+        return;
+    }
+
+    // left/right step values, enter flips switches and cycles choices forward
+    let step = if kb.just_pressed(KeyCode::Right) || kb.just_pressed(KeyCode::Return) {
+        1
+    } else if kb.just_pressed(KeyCode::Left) {
+        -1
+    } else {
+        return;
+    };
+
+    let mut changed = settings.clone();
+    let volume = |value: f32| (value + step as f32 * VOLUME_STEP).clamp(0., 1.);
+    match item {
+        SettingsItem::Fullscreen => {
+            if let Some(window) = windows.get_primary_mut() {
+                window.set_mode(match window.mode() {
+                    WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+                    _ => WindowMode::Windowed,
+                });
+            }
+        }
+        SettingsItem::Vsync => changed.vsync = !changed.vsync,
+        SettingsItem::MasterVolume => changed.master_volume = volume(changed.master_volume),
+        SettingsItem::MusicVolume => changed.music_volume = volume(changed.music_volume),
+        SettingsItem::SfxVolume => changed.sfx_volume = volume(changed.sfx_volume),
+        SettingsItem::Shake => {
+            changed.shake = (changed.shake + step as f32 * SHAKE_STEP).clamp(0., 1.)
+        }
+        SettingsItem::ReducedMotion => changed.reduced_motion = !changed.reduced_motion,
+        SettingsItem::AutoFire => changed.auto_fire = !changed.auto_fire,
+        SettingsItem::Drones => {
+            changed.drone_mode = cycle(&DroneMode::ALL, changed.drone_mode, step)
+        }
+        SettingsItem::Difficulty => {
+            changed.difficulty = cycle(&Difficulty::ALL, changed.difficulty, step)
+        }
+        SettingsItem::Language => changed.language = cycle(&Language::ALL, changed.language, step),
+        SettingsItem::Skin => {
+            if let Some(skin) = cycle_by(&skins.names, |name| *name == changed.skin, step) {
+                changed.skin = skin.clone();
+            }
+        }
+        SettingsItem::Back => {}
+    }
+
+    if changed != *settings {
+        *settings = changed;
+        settings.save();
+    }
+}
+
+// spawns and removes the overlay, title and hint are only written when it is built
+fn settings_menu_ui_system(
+    mut commands: Commands,
+    fonts: Res<GameFonts>,
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    query: Query<(Entity, &SettingsUi)>,
+) {
+    let lang = settings.language;
+    let mut built = false;
+    for (entity, ui) in query.iter() {
+        if menu.open && ui.0 == lang {
+            built = true;
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    if !menu.open || built {
+        return;
+    }
+
+    commands
+        .spawn_bundle(screen_root())
+        .insert(SettingsUi(lang))
+        .with_children(|parent| {
+            parent.spawn_bundle(label(&fonts, lang.tr("SETTINGS"), 48., Color::WHITE));
+            for index in 0..SettingsItem::ALL.len() {
+                parent
+                    .spawn_bundle(label(&fonts, "", 24., Color::WHITE))
+                    .insert(SettingsRow(index));
+            }
+            parent.spawn_bundle(label(
+                &fonts,
+                lang.tr("UP/DOWN select   LEFT/RIGHT change   ESC back"),
+                18.,
+                Color::GRAY,
+            ));
+        });
+}
+
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: i32) -> T {
+    *cycle_by(all, |value| *value == current, step).unwrap_or(&current)
+}
+
+// neighbour of the current entry, None if there is nothing to pick
+fn cycle_by<T>(all: &[T], is_current: impl Fn(&T) -> bool, step: i32) -> Option<&T> {
+    if all.is_empty() {
+        return None;
+    }
+    let index = all.iter().position(is_current).unwrap_or(0) as i32;
+    Some(&all[(index + step).rem_euclid(all.len() as i32) as usize])
+}
+
+// redrawn every frame, the window mode can also change through F11
+fn settings_menu_text_system(
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    mut query: Query<(&SettingsRow, &mut Text)>,
+) {
+    let lang = settings.language;
+    let switch = |on: bool| lang.tr(if on { "ON" } else { "OFF" });
+    let percent = |value: f32| format!("{:.0}%", value * 100.);
+    let fullscreen = windows
+        .get_primary()
+        .is_some_and(|window| window.mode() != WindowMode::Windowed);
+
+    for (row, mut text) in query.iter_mut() {
+        let item = SettingsItem::ALL[row.0];
+        let value = match item {
+            SettingsItem::Fullscreen => switch(fullscreen).to_string(),
+            SettingsItem::Vsync => switch(settings.vsync).to_string(),
+            SettingsItem::MasterVolume => percent(settings.master_volume),
+            SettingsItem::MusicVolume => percent(settings.music_volume),
+            SettingsItem::SfxVolume => percent(settings.sfx_volume),
+            SettingsItem::Shake => percent(settings.shake),
+            SettingsItem::ReducedMotion => switch(settings.reduced_motion).to_string(),
+            SettingsItem::AutoFire => switch(settings.auto_fire).to_string(),
+            SettingsItem::Drones => lang.tr(settings.drone_mode.name()).to_string(),
+            SettingsItem::Difficulty => lang.tr(settings.difficulty.name()).to_string(),
+            SettingsItem::Language => settings.language.name().to_string(),
+            SettingsItem::Skin => settings.skin.clone(),
+            SettingsItem::Back => String::new(),
+        };
+        let section = &mut text.sections[0];
+        section.value = if value.is_empty() {
+            lang.tr(item.name()).to_string()
+        } else {
+            format!("{}   < {} >", lang.tr(item.name()), value)
+        };
+        section.style.color = if row.0 == menu.selected {
+            Color::rgb(1., 0.85, 0.3)
+        } else {
+            Color::WHITE
+        };
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    components::SpriteSize, loading::LoadingAssets, settings::Settings, GameTextures,
+    BUILTIN_SKINS, PLAYER_ALT_SPRITE, SKIN_DIR,
+};
+
+// -- skin packs map the sprite roles to image files. every *.skin.ron in assets/skins
+// is picked up, so mods only have to drop their pack (and images) in there
+
+pub struct SkinPlugin;
+
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SkinPack>()
+            .init_asset_loader::<SkinPackLoader>()
+            .insert_resource(Skins::default())
+            .add_startup_system(skin_load_system)
+            .add_system(skin_preload_system)
+            .add_system(skin_list_system)
+            .add_system(skin_apply_system.after(skin_list_system));
+    }
+}
+
+// manifest of one pack, image paths are relative to assets/
+#[derive(Deserialize, TypeUuid, Clone, PartialEq, Debug)]
+#[uuid = "0d6c2a57-3e4b-4f1a-a8c9-71b2f4e5d630"]
+pub struct SkinPack {
+    pub name: String, // shown in the menu, also what the settings remember
+    pub player: String,
+    #[serde(default = "default_player_alt")]
+    pub player_alt: String, // second ship, packs from before it existed keep the stock one
+    pub player_laser: String,
+    pub enemy: String,
+    pub enemy_laser: String,
+}
+
+impl SkinPack {
+    fn images(&self) -> [&String; 5] {
+        [
+            &self.player,
+            &self.player_alt,
+            &self.player_laser,
+            &self.enemy,
+            &self.enemy_laser,
+        ]
+    }
+}
+
+// -Ressource - every pack found, names in menu order
+#[derive(Default)]
+pub struct Skins {
+    handles: Vec<Handle<SkinPack>>,
+    unread: Vec<Handle<SkinPack>>, // images not handed to the loading screen yet
+    pub names: Vec<String>,
+}
+
+fn default_player_alt() -> String {
+    PLAYER_ALT_SPRITE.to_string()
+}
+
+#[derive(Default)]
+struct SkinPackLoader;
+
+impl AssetLoader for SkinPackLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let pack: SkinPack = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(pack));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skin.ron"]
+    }
+}
+
+fn skin_load_system(
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+    mut skins: ResMut<Skins>,
+) {
+    skins.handles = match asset_server.load_folder(SKIN_DIR) {
+        Ok(handles) if !handles.is_empty() => {
+            handles.into_iter().map(|handle| handle.typed()).collect()
+        }
+        // the browser can't list folders, only the packs we ship
+        _ => BUILTIN_SKINS
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    };
+
+    // a broken pack stops on the error screen like any other asset
+    for handle in skins.handles.iter() {
+        let path = asset_server.get_handle_path(handle).map_or_else(
+            || SKIN_DIR.to_string(),
+            |path| path.path().display().to_string(),
+        );
+        loading.track(path, handle.clone_untyped());
+    }
+    loading.expect(skins.handles.len());
+    skins.unread = skins.handles.clone();
+}
+
+// the images of every pack go through the loading screen as soon as the pack is in
+fn skin_preload_system(
+    asset_server: Res<AssetServer>,
+    packs: Res<Assets<SkinPack>>,
+    mut loading: ResMut<LoadingAssets>,
+    mut skins: ResMut<Skins>,
+) {
+    if skins.unread.is_empty() {
+        return;
+    }
+    let skins = &mut *skins;
+    skins.unread.retain(|handle| {
+        let pack = match packs.get(handle) {
+            Some(pack) => pack,
+            None => return true,
+        };
+        for path in pack.images() {
+            loading.load::<Image>(&asset_server, path);
+        }
+        loading.resolve();
+        false
+    });
+}
+
+fn skin_list_system(
+    packs: Res<Assets<SkinPack>>,
+    mut skins: ResMut<Skins>,
+    mut events: EventReader<AssetEvent<SkinPack>>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    let mut names: Vec<String> = skins
+        .handles
+        .iter()
+        .filter_map(|handle| packs.get(handle))
+        .map(|pack| pack.name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    skins.names = names;
+}
+
+// switches GameTextures to the picked pack and swaps the art of everything already out
+fn skin_apply_system(
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    settings: Res<Settings>,
+    skins: Res<Skins>,
+    packs: Res<Assets<SkinPack>>,
+    mut game_textures: ResMut<GameTextures>,
+    mut applied: Local<Option<SkinPack>>,
+    mut query: Query<(&mut Handle<Image>, Option<&mut SpriteSize>)>,
+) {
+    let pack = match skins
+        .handles
+        .iter()
+        .filter_map(|handle| packs.get(handle))
+        .find(|pack| pack.name == settings.skin)
+    {
+        Some(pack) => pack,
+        None => return, // not loaded (yet), the built in textures stay
+    };
+    if applied.as_ref() == Some(pack) {
+        return;
+    }
+
+    // only swap once every image is in, a pack edited while running loads here
+    let handles = pack
+        .images()
+        .map(|path| asset_server.load::<Image, _>(path.as_str()));
+    if handles
+        .iter()
+        .any(|handle| asset_server.get_load_state(handle) != LoadState::Loaded)
+        || handles.iter().any(|handle| images.get(handle).is_none())
+    {
+        return;
+    }
+
+    let game_textures = &mut *game_textures;
+    let textures = [
+        &mut game_textures.player,
+        &mut game_textures.player_alt,
+        &mut game_textures.player_laser,
+        &mut game_textures.enemy,
+        &mut game_textures.enemy_laser,
+    ];
+    let swaps: Vec<(Handle<Image>, Handle<Image>)> = textures
+        .into_iter()
+        .zip(handles)
+        .map(|(texture, new)| (std::mem::replace(texture, new.clone()), new))
+        .collect();
+
+    // pooled entities too, they keep their texture when reused
+    for (mut image, sprite_size) in query.iter_mut() {
+        if let Some((_, new)) = swaps.iter().find(|(old, _)| *old == *image) {
+            *image = new.clone();
+            // keeps colliding, now with the size of the new art
+            if let (Some(mut sprite_size), Some(new_image)) = (sprite_size, images.get(new)) {
+                sprite_size.0 = new_image.size();
+            }
+        }
+    }
+    *applied = Some(pack.clone());
+}
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+use bevy::{audio::AudioSink, prelude::*, utils::HashMap};
+
+use self::music::{
+    music_crossfade_system, music_cue_system, music_layer_system, MusicLayers, MusicVoices,
+};
+use crate::{
+    events::{EnemyKilled, PlayerDied, PlayerHit, ShotFired},
+    loading::LoadingAssets,
+    ENEMY_LASER_SFX, EXPLOSION_SFX, HIT_SFX, PICKUP_SFX, PLAYER_DEATH_SFX, PLAYER_LASER_SFX,
+    SFX_MAX_COPIES,
+};
+
+mod music;
+
+pub use self::music::{MusicCue, MusicTrack};
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AudioVolume::default())
+            .insert_resource(MusicCue(MusicTrack::Title))
+            .insert_resource(SfxVoices::default())
+            .insert_resource(MusicVoices::default())
+            .insert_resource(MusicLayers::default())
+            .add_event::<PlaySfx>()
+            .add_startup_system(sound_setup_system)
+            .add_system(sfx_from_events_system.before(sfx_play_system))
+            .add_system(sfx_play_system)
+            .add_system(music_cue_system)
+            .add_system(music_layer_system)
+            .add_system(
+                music_crossfade_system
+                    .after(music_cue_system)
+                    .after(music_layer_system),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Sfx {
+    PlayerLaser,
+    EnemyLaser,
+    Hit,
+    Explosion,
+    Pickup,
+    PlayerDeath,
+}
+
+impl Sfx {
+    const ALL: [Sfx; 6] = [
+        Sfx::PlayerLaser,
+        Sfx::EnemyLaser,
+        Sfx::Hit,
+        Sfx::Explosion,
+        Sfx::Pickup,
+        Sfx::PlayerDeath,
+    ];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Sfx::PlayerLaser => PLAYER_LASER_SFX,
+            Sfx::EnemyLaser => ENEMY_LASER_SFX,
+            Sfx::Hit => HIT_SFX,
+            Sfx::Explosion => EXPLOSION_SFX,
+            Sfx::Pickup => PICKUP_SFX,
+            Sfx::PlayerDeath => PLAYER_DEATH_SFX,
+        }
+    }
+
+    // seconds, sinks can't tell when they are done so we count on this
+    fn len(&self) -> f64 {
+        match self {
+            Sfx::PlayerLaser => 0.15,
+            Sfx::EnemyLaser => 0.2,
+            Sfx::Hit => 0.25,
+            Sfx::Explosion => 0.6,
+            Sfx::Pickup => 0.3,
+            Sfx::PlayerDeath => 1.2,
+        }
+    }
+
+    // mix level on top of the sfx bus, the lasers fire a lot
+    fn gain(&self) -> f32 {
+        match self {
+            Sfx::PlayerLaser | Sfx::EnemyLaser => 0.5,
+            _ => 1.,
+        }
+    }
+}
+
+// -Event - anyone can ask for a sound (pickups, menus, ...)
+pub struct PlaySfx(pub Sfx);
+
+// region: --- Resources
+// volume buses, 0..1
+pub struct AudioVolume {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioVolume {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            music: 0.6,
+            sfx: 0.8,
+        }
+    }
+}
+
+impl AudioVolume {
+    pub fn music(&self) -> f32 {
+        self.master * self.music
+    }
+
+    pub fn sfx(&self) -> f32 {
+        self.master * self.sfx
+    }
+}
+
+struct GameSounds {
+    sfx: HashMap<Sfx, Handle<AudioSource>>,
+    music: HashMap<MusicTrack, Vec<Handle<AudioSource>>>, // stems
+}
+
+// playing copies per sound, with the time they end
+#[derive(Default)]
+struct SfxVoices(HashMap<Sfx, VecDeque<(Handle<AudioSink>, f64)>>);
+
+// endregion: --- Resources
+
+fn sound_setup_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    commands.insert_resource(GameSounds {
+        sfx: Sfx::ALL
+            .iter()
+            .map(|sfx| (*sfx, loading.load(&asset_server, sfx.path())))
+            .collect(),
+        music: MusicTrack::ALL
+            .iter()
+            .map(|track| {
+                let stems = track.stems().iter();
+                (
+                    *track,
+                    stems
+                        .map(|stem| loading.load(&asset_server, stem))
+                        .collect(),
+                )
+            })
+            .collect(),
+    });
+}
+
+fn sfx_from_events_system(
+    mut sfx_events: EventWriter<PlaySfx>,
+    mut shot_events: EventReader<ShotFired>,
+    mut hit_events: EventReader<PlayerHit>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut died_events: EventReader<PlayerDied>,
+) {
+    for shot in shot_events.iter() {
+        sfx_events.send(PlaySfx(if shot.from_player {
+            Sfx::PlayerLaser
+        } else {
+            Sfx::EnemyLaser
+        }));
+    }
+    for _ in hit_events.iter() {
+        sfx_events.send(PlaySfx(Sfx::Hit));
+    }
+    for _ in killed_events.iter() {
+        sfx_events.send(PlaySfx(Sfx::Explosion));
+    }
+    for _ in died_events.iter() {
+        sfx_events.send(PlaySfx(Sfx::PlayerDeath));
+    }
+}
+
+fn sfx_play_system(
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+    sounds: Option<Res<GameSounds>>,
+    volume: Res<AudioVolume>,
+    time: Res<Time>,
+    mut voices: ResMut<SfxVoices>,
+    mut sfx_events: EventReader<PlaySfx>,
+) {
+    let sounds = match sounds {
+        Some(sounds) => sounds,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+
+    for PlaySfx(sfx) in sfx_events.iter() {
+        let playing = voices.0.entry(*sfx).or_default();
+        playing.retain(|(_, ends_at)| *ends_at > now);
+
+        // too many copies, cut the oldest one
+        if playing.len() >= SFX_MAX_COPIES {
+            if let Some((oldest, _)) = playing.pop_front() {
+                if let Some(sink) = sinks.get(&oldest) {
+                    sink.stop();
+                }
+            }
+        }
+
+        let sink = audio.play_with_settings(
+            sounds.sfx[sfx].clone(),
+            PlaybackSettings::ONCE.with_volume(volume.sfx() * sfx.gain()),
+        );
+        playing.push_back((sinks.get_handle(sink), now + sfx.len()));
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::{audio::AudioSink, prelude::*};
+
+use super::{AudioVolume, GameSounds};
+use crate::{
+    score::Scores, AppState, EnemyCount, PlayerStates, GAMEPLAY_MUSIC, MUSIC_BAR_LEN,
+    MUSIC_CROSSFADE, MUSIC_DRUMS_ENEMIES, MUSIC_HIGH_ENEMIES, MUSIC_LAYER_FADE, MUSIC_LEAD_COMBO,
+    TITLE_MUSIC,
+};
+
+const LAYER_COUNT: usize = 4; // base, drums, lead, high
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MusicTrack {
+    Title,
+    Gameplay,
+}
+
+impl MusicTrack {
+    pub const ALL: [MusicTrack; 2] = [MusicTrack::Title, MusicTrack::Gameplay];
+
+    /// stem files in layer order, tracks can have fewer stems than layers
+    pub fn stems(&self) -> &'static [&'static str] {
+        match self {
+            MusicTrack::Title => TITLE_MUSIC,
+            MusicTrack::Gameplay => GAMEPLAY_MUSIC,
+        }
+    }
+}
+
+// region: --- Resources
+// track that should be playing, follows the app state
+pub struct MusicCue(pub MusicTrack);
+
+// layers the current situation asks for, applied on the next bar line
+#[derive(Default)]
+pub(super) struct MusicLayers([bool; LAYER_COUNT]);
+
+struct Stem {
+    sink: Handle<AudioSink>,
+    mix: f32, // 0..1
+    on: bool,
+}
+
+struct MusicVoice {
+    track: MusicTrack,
+    stems: Vec<Stem>, // empty until every stem is loaded
+    started_at: f64,
+    bar: u64,
+    fade: f32, // crossfade between tracks, 0..1
+    fading_in: bool,
+}
+
+#[derive(Default)]
+pub(super) struct MusicVoices(Vec<MusicVoice>);
+// endregion: --- Resources
+
+pub(super) fn music_cue_system(state: Res<State<AppState>>, mut cue: ResMut<MusicCue>) {
+    if !state.is_changed() {
+        return;
+    }
+    let track = match state.current() {
+        AppState::Playing => MusicTrack::Gameplay,
+        _ => MusicTrack::Title,
+    };
+    if cue.0 != track {
+        cue.0 = track;
+    }
+}
+
+pub(super) fn music_layer_system(
+    enemy_count: Res<EnemyCount>,
+    player_states: Res<PlayerStates>,
+    scores: Res<Scores>,
+    mut layers: ResMut<MusicLayers>,
+) {
+    let enemies = enemy_count.0;
+    let last_life = player_states.joined().any(|state| state.lives <= 1);
+    let wanted = [
+        true,
+        enemies >= MUSIC_DRUMS_ENEMIES,
+        scores.best_multiplier() >= MUSIC_LEAD_COMBO,
+        // anyone on the last life (or out) counts as intense too
+        enemies >= MUSIC_HIGH_ENEMIES || last_life,
+    ];
+    if layers.0 != wanted {
+        layers.0 = wanted;
+    }
+}
+
+pub(super) fn music_crossfade_system(
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+    sources: Res<Assets<AudioSource>>,
+    sounds: Option<Res<GameSounds>>,
+    cue: Res<MusicCue>,
+    layers: Res<MusicLayers>,
+    volume: Res<AudioVolume>,
+    time: Res<Time>,
+    mut voices: ResMut<MusicVoices>,
+) {
+    let sounds = match sounds {
+        Some(sounds) => sounds,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+
+    // fade out everything else, bring in the cued track if it isn't around anymore
+    if cue.is_changed() {
+        for voice in voices.0.iter_mut() {
+            voice.fading_in = voice.track == cue.0;
+        }
+        if !voices.0.iter().any(|voice| voice.track == cue.0) {
+            voices.0.push(MusicVoice {
+                track: cue.0,
+                stems: Vec::new(),
+                started_at: now,
+                bar: 0,
+                fade: 0.,
+                fading_in: true,
+            });
+        }
+    }
+
+    let fade_step = time.delta_seconds() / MUSIC_CROSSFADE;
+    let mix_step = time.delta_seconds() / MUSIC_LAYER_FADE;
+    for voice in voices.0.iter_mut() {
+        // start every stem in the same frame, else they drift apart
+        let handles = &sounds.music[&voice.track];
+        if voice.stems.is_empty() && handles.iter().all(|h| sources.get(h).is_some()) {
+            voice.stems = handles
+                .iter()
+                .zip(layers.0)
+                .map(|(handle, on)| {
+                    let settings = PlaybackSettings::LOOP.with_volume(0.);
+                    Stem {
+                        sink: sinks.get_handle(audio.play_with_settings(handle.clone(), settings)),
+                        mix: if on { 1. } else { 0. },
+                        on,
+                    }
+                })
+                .collect();
+            voice.started_at = now;
+            voice.bar = 0;
+        }
+
+        // layers only switch on bar lines
+        let bar = ((now - voice.started_at) / MUSIC_BAR_LEN) as u64;
+        if bar != voice.bar {
+            voice.bar = bar;
+            for (stem, on) in voice.stems.iter_mut().zip(layers.0) {
+                stem.on = on;
+            }
+        }
+
+        voice.fade = if voice.fading_in {
+            (voice.fade + fade_step).min(1.)
+        } else {
+            (voice.fade - fade_step).max(0.)
+        };
+        for stem in voice.stems.iter_mut() {
+            stem.mix = if stem.on {
+                (stem.mix + mix_step).min(1.)
+            } else {
+                (stem.mix - mix_step).max(0.)
+            };
+            if let Some(sink) = sinks.get(&stem.sink) {
+                sink.set_volume(voice.fade * stem.mix * volume.music());
+            }
+        }
+    }
+
+    // stop faded out tracks. dropping a sink that hasn't started yet would leave it looping
+    voices.0.retain(|voice| {
+        if voice.fading_in || voice.fade > 0. {
+            return true;
+        }
+        if voice
+            .stems
+            .iter()
+            .any(|stem| sinks.get(&stem.sink).is_none())
+        {
+            return true;
+        }
+        for stem in voice.stems.iter() {
+            if let Some(sink) = sinks.get(&stem.sink) {
+                sink.stop();
+            }
+        }
+        false
+    });
+}
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use crate::GameFonts;
+
+// -- shared builders for the full screen menus and overlays
+
+/// full screen column, children centered
+pub fn screen_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse, // top to bottom
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: Color::rgba(0., 0., 0., 0.7).into(),
+        ..Default::default()
+    }
+}
+
+pub fn label(fonts: &GameFonts, value: impl Into<String>, size: f32, color: Color) -> TextBundle {
+    font_label(fonts.main.clone(), value, size, color)
+}
+
+/// label with a font that is not in GameFonts
+pub fn font_label(
+    font: Handle<Font>,
+    value: impl Into<String>,
+    size: f32,
+    color: Color,
+) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font,
+            font_size: size,
+            color,
+        },
+    )
+    .with_style(Style {
+        margin: UiRect::all(Val::Px(6.)),
+        ..Default::default()
+    })
+}
+
+/// removes every screen tagged with T, use as on_exit system
+pub fn despawn_screen<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
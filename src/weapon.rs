@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        Collider, Damage, DespawnPolicy, Enemy, FromPlayer, Laser, Movable, Owner, Piercing,
+        Player, PlayerId, SpriteSize, Swept, Velocity,
+    },
+    controls::PlayerInputs,
+    pool::Pooled,
+    sound::{PlaySfx, Sfx},
+    AppState, GameClock, GameConfig, GameTextures, BEAM_DAMAGE, BEAM_SIZE, BEAM_SPEED, CHARGE_MIN,
+    CHARGE_TIME, MISSILE_COOLDOWN, MISSILE_DAMAGE, MISSILE_SPEED, MISSILE_TURN_RATE,
+    PLAYER_LASER_HITBOX, PROJECTILE_DESPAWN_MARGIN, SWEPT_SPEED_MIN,
+};
+
+const CHARGE_METER_HEIGHT: f32 = 5.; // px
+
+// -- fire modes on top of the normal shot: holding fire charges a piercing beam
+// that goes off on release, the secondary button launches homing missiles
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::Playing)
+                .with_system(weapon_setup_system)
+                .with_system(charge_system)
+                .with_system(charge_meter_system.after(charge_system))
+                .with_system(missile_fire_system)
+                .with_system(homing_system),
+        );
+    }
+}
+
+// region: --- Components
+// -- Component - on the player, added once it is out
+#[derive(Component, Default)]
+pub struct WeaponState {
+    charge_started: Option<f64>, // while fire is held
+    missile_ready_at: f64,
+}
+
+impl WeaponState {
+    /// 0..1, full after CHARGE_TIME
+    fn charge(&self, now: f64) -> f32 {
+        self.charge_started
+            .map_or(0., |started| ((now - started) / CHARGE_TIME).min(1.) as f32)
+    }
+
+    /// held long enough for a beam, the normal shot holds back meanwhile
+    pub fn charging(&self, now: f64) -> bool {
+        self.charge(now) >= CHARGE_MIN
+    }
+}
+
+// child of the player, fills up below the ship
+#[derive(Component)]
+struct ChargeMeter;
+
+// turns towards the closest enemy
+#[derive(Component)]
+pub struct Homing;
+// endregion: --- Components
+
+fn weapon_setup_system(
+    mut commands: Commands,
+    query: Query<Entity, (With<Player>, Without<WeaponState>)>,
+) {
+    for entity in query.iter() {
+        commands
+            .entity(entity)
+            .insert(WeaponState::default())
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        visibility: Visibility { is_visible: false },
+                        ..Default::default()
+                    })
+                    .insert(ChargeMeter);
+            });
+    }
+}
+
+fn charge_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    inputs: Res<PlayerInputs>,
+    config: Res<GameConfig>,
+    mut sfx_events: EventWriter<PlaySfx>,
+    mut query: Query<
+        (
+            Entity,
+            &PlayerId,
+            &Transform,
+            Option<&SpriteSize>,
+            &mut WeaponState,
+        ),
+        With<Player>,
+    >,
+) {
+    let now = clock.elapsed();
+    for (entity, player_id, transform, size, mut weapon) in query.iter_mut() {
+        let input = inputs.0[player_id.0];
+        if input.fire_pressed {
+            weapon.charge_started = Some(now);
+        }
+        if !input.fire_released {
+            if !input.fire {
+                weapon.charge_started = None;
+            }
+            continue;
+        }
+
+        let charge = weapon.charge(now);
+        weapon.charge_started = None;
+        if charge < CHARGE_MIN {
+            continue;
+        }
+
+        // grows with the charge, so does the damage
+        let beam_size = Vec2::new(BEAM_SIZE.0 * (0.5 + charge / 2.), BEAM_SIZE.1 * charge);
+        let top = transform.translation.y
+            + beam_size.y / 2.
+            + size.map_or(0., |ship| ship.0.y / 2. * config.sprite_scale);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.6, 0.9, 1., 0.85),
+                    custom_size: Some(beam_size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(Vec3::new(transform.translation.x, top, 2.)),
+                ..Default::default()
+            })
+            .insert(Laser)
+            .insert(FromPlayer)
+            .insert(Owner(entity))
+            .insert(*player_id)
+            .insert(Collider::player_laser())
+            .insert(SpriteSize(beam_size)) // no image to take it from
+            .insert(Damage((BEAM_DAMAGE as f32 * charge).ceil() as u32))
+            .insert(Piercing::default())
+            .insert(Movable)
+            .insert(Velocity {
+                x: 0.,
+                y: BEAM_SPEED,
+            })
+            .insert(DespawnPolicy::OffScreen {
+                margin: beam_size.y,
+            });
+        sfx_events.send(PlaySfx(Sfx::PlayerLaser));
+    }
+}
+
+fn charge_meter_system(
+    clock: Res<GameClock>,
+    config: Res<GameConfig>,
+    player_query: Query<(&WeaponState, Option<&SpriteSize>, &Children), With<Player>>,
+    mut meter_query: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<ChargeMeter>>,
+) {
+    let now = clock.elapsed();
+    for (weapon, size, children) in player_query.iter() {
+        let charge = weapon.charge(now);
+        let ship = size.map_or(Vec2::ZERO, |size| size.0);
+        for child in children.iter() {
+            if let Ok((mut sprite, mut transform, mut visibility)) = meter_query.get_mut(*child) {
+                visibility.is_visible = charge > 0.;
+                // child of the scaled ship, sizes are in sprite pixels
+                let height = CHARGE_METER_HEIGHT / config.sprite_scale;
+                sprite.custom_size = Some(Vec2::new(ship.x * charge, height));
+                sprite.color = if charge >= 1. {
+                    Color::rgb(1., 0.85, 0.3)
+                } else if charge >= CHARGE_MIN {
+                    Color::rgb(0.6, 0.9, 1.)
+                } else {
+                    Color::GRAY
+                };
+                transform.translation = Vec3::new(0., -ship.y / 2. + height / 2., 0.1);
+            }
+        }
+    }
+}
+
+fn missile_fire_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    inputs: Res<PlayerInputs>,
+    config: Res<GameConfig>,
+    game_textures: Res<GameTextures>,
+    mut sfx_events: EventWriter<PlaySfx>,
+    mut query: Query<(Entity, &PlayerId, &Transform, &mut WeaponState), With<Player>>,
+) {
+    let now = clock.elapsed();
+    for (entity, player_id, transform, mut weapon) in query.iter_mut() {
+        if !inputs.0[player_id.0].secondary || now < weapon.missile_ready_at {
+            continue;
+        }
+        weapon.missile_ready_at = now + MISSILE_COOLDOWN;
+
+        // a pair, leaving to both sides before they turn in
+        for side in [-1., 1.] {
+            let direction = Vec2::new(side * 0.5, 1.).normalize() * MISSILE_SPEED;
+            let translation = transform.translation + Vec3::new(side * 12., 0., 1.);
+            let mut missile = commands.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(1., 0.6, 0.3),
+                    ..Default::default()
+                },
+                texture: game_textures.player_laser.clone(),
+                transform: Transform {
+                    translation,
+                    rotation: Quat::from_rotation_z(-direction.x.atan2(direction.y)),
+                    scale: Vec3::new(config.sprite_scale, config.sprite_scale, 1.),
+                },
+                ..Default::default()
+            });
+            missile
+                .insert(Laser)
+                .insert(FromPlayer)
+                .insert(Owner(entity))
+                .insert(*player_id)
+                .insert(Collider::player_laser())
+                .insert(config.hitbox("player_laser", PLAYER_LASER_HITBOX))
+                .insert(Damage(MISSILE_DAMAGE))
+                .insert(Homing)
+                .insert(Movable)
+                .insert(Velocity {
+                    x: direction.x,
+                    y: direction.y,
+                })
+                .insert(DespawnPolicy::OffScreen {
+                    margin: PROJECTILE_DESPAWN_MARGIN,
+                });
+            if MISSILE_SPEED * config.base_speed > SWEPT_SPEED_MIN {
+                missile.insert(Swept::from(translation));
+            }
+        }
+        sfx_events.send(PlaySfx(Sfx::PlayerLaser));
+    }
+}
+
+// limited turn rate, so missiles can still miss
+fn homing_system(
+    clock: Res<GameClock>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Homing>)>,
+    mut query: Query<(&mut Velocity, &mut Transform), (With<Homing>, Without<Pooled>)>,
+) {
+    let max_turn = MISSILE_TURN_RATE * clock.delta_seconds();
+    for (mut velocity, mut transform) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        let current = Vec2::new(velocity.x, velocity.y);
+        let closest = enemy_query
+            .iter()
+            .map(|enemy_tf| enemy_tf.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+
+        if let Some(target) = closest {
+            let wanted = target - position;
+            if wanted != Vec2::ZERO {
+                let turn = current.angle_between(wanted).clamp(-max_turn, max_turn);
+                let turned = Vec2::from_angle(turn).rotate(current);
+                velocity.x = turned.x;
+                velocity.y = turned.y;
+            }
+        }
+        transform.rotation = Quat::from_rotation_z(-velocity.x.atan2(velocity.y));
+    }
+}